@@ -0,0 +1,80 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+/// User configuration read from `$XDG_CONFIG_HOME/tempus/config.toml`, falling
+/// back to `~/.config/tempus/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// Custom presets mapping a name to a duration string, e.g. `deepwork = "50m"`
+    #[serde(default)]
+    pub presets: BTreeMap<String, String>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Focus mode key bindings mapping an action to a key, e.g. `add = "k"`
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+}
+
+/// The `[theme]` table.
+#[derive(Debug, Default, Deserialize)]
+pub struct ThemeConfig {
+    /// Colors for `--theme custom`.
+    pub custom: Option<CustomThemeConfig>,
+}
+
+/// The `[theme.custom]` table, with colors given as hex strings like `"#00ff00"`.
+#[derive(Debug, Default, Deserialize)]
+pub struct CustomThemeConfig {
+    /// Gradient stops from the start of the bar to the end
+    #[serde(default)]
+    pub gradient: Vec<String>,
+    pub spinner: Option<String>,
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_home.join("tempus").join("config.toml"))
+}
+
+/// Load the user configuration. A missing file yields the defaults, and an
+/// unreadable or malformed one is reported on stderr and otherwise ignored.
+pub fn load_config() -> Config {
+    config_path()
+        .map(|path| load_config_from(&path))
+        .unwrap_or_default()
+}
+
+pub fn load_config_from(path: &Path) -> Config {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Config::default(),
+        Err(e) => {
+            eprintln!("Warning: could not read {}: {}", path.display(), e);
+            return Config::default();
+        }
+    };
+
+    toml::from_str(&contents).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: ignoring malformed config {}: {}",
+            path.display(),
+            e
+        );
+        Config::default()
+    })
+}
+
+/// Read a `--preset-file`: a TOML file of `name = "duration"` pairs. Unlike
+/// the config file it was asked for explicitly, so a missing or malformed
+/// file is an error rather than a warning.
+pub fn load_preset_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,308 @@
+use crate::{Result, TempusError};
+#[cfg(unix)]
+use serde_json::json;
+use std::fs;
+#[cfg(unix)]
+use std::io::{self, BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+#[cfg(unix)]
+use std::thread;
+use std::time::Duration;
+
+/// Longest a timer with a control socket goes without answering commands.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the listener checks for new clients and whether the timer is over.
+#[cfg(unix)]
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a client waits for the timer to answer before giving up.
+#[cfg(unix)]
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A line command read from `--control-socket`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Only reply with the current status.
+    Status,
+    Pause,
+    Resume,
+    /// Give the timer this much more time.
+    Add(Duration),
+    /// Stop the timer, like pressing `q`.
+    Quit,
+}
+
+#[cfg(unix)]
+impl ControlCommand {
+    /// Parse one line such as `pause` or `add 60`. The amount for `add` is in
+    /// seconds, or any duration `tempus` accepts, e.g. `add 5m`.
+    pub fn parse(line: &str) -> std::result::Result<ControlCommand, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let argument = words.next();
+        if words.next().is_some() {
+            return Err(format!("too many arguments in '{}'", line.trim()));
+        }
+
+        match (command, argument) {
+            ("status", None) => Ok(ControlCommand::Status),
+            ("pause", None) => Ok(ControlCommand::Pause),
+            ("resume", None) => Ok(ControlCommand::Resume),
+            ("quit", None) => Ok(ControlCommand::Quit),
+            ("add", Some(amount)) => parse_amount(amount).map(ControlCommand::Add),
+            ("add", None) => Err("add needs an amount, e.g. 'add 60'".to_string()),
+            ("status" | "pause" | "resume" | "quit", Some(_)) => {
+                Err(format!("{} takes no argument", command))
+            }
+            _ => Err(format!("unknown command '{}'", command)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn parse_amount(amount: &str) -> std::result::Result<Duration, String> {
+    amount
+        .parse::<u64>()
+        .map(Duration::from_secs)
+        .or_else(|_| crate::parse_duration(amount).map_err(|e| e.to_string()))
+}
+
+/// The JSON line sent back for a command that couldn't be carried out.
+#[cfg(unix)]
+fn error_reply(message: &str) -> String {
+    json!({ "error": message }).to_string()
+}
+
+/// A command from a client, along with the way back to it.
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    reply: Sender<String>,
+}
+
+impl ControlRequest {
+    /// Answer the client with `status`, a line of JSON. A client that has
+    /// already hung up is ignored.
+    pub fn reply(self, status: String) {
+        let _ = self.reply.send(status);
+    }
+}
+
+/// The listening end of `--control-socket`. Clients are served on background
+/// threads, which hand each command to the timer through a channel, so the
+/// timer's own state is only ever touched by the timer loop. Dropping it stops
+/// listening and removes the socket file.
+pub struct ControlSocket {
+    requests: Receiver<ControlRequest>,
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+impl ControlSocket {
+    /// Listen on `path`. A socket file left behind by a timer that didn't exit
+    /// cleanly is replaced, but not one that is still in use, and never a file
+    /// that isn't a socket.
+    #[cfg(unix)]
+    pub fn bind(path: &Path) -> Result<ControlSocket> {
+        let error = |e: io::Error| TempusError::ControlSocket(format!("{}: {}", path.display(), e));
+
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(TempusError::ControlSocket(format!(
+                    "{} exists and is not a socket",
+                    path.display()
+                )));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(TempusError::ControlSocket(format!(
+                    "{} is already in use",
+                    path.display()
+                )));
+            }
+            fs::remove_file(path).map_err(error)?;
+        }
+
+        let listener = UnixListener::bind(path).map_err(error)?;
+        listener.set_nonblocking(true).map_err(error)?;
+
+        let (sender, requests) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let listener_stop = Arc::clone(&stop);
+        thread::spawn(move || accept_clients(listener, sender, &listener_stop));
+
+        Ok(ControlSocket {
+            requests,
+            path: path.to_path_buf(),
+            stop,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(_path: &Path) -> Result<ControlSocket> {
+        Err(TempusError::ControlSocket(
+            "control sockets are only supported on Unix".to_string(),
+        ))
+    }
+
+    /// The next command waiting to be carried out, if any.
+    pub fn try_recv(&self) -> Option<ControlRequest> {
+        self.requests.try_recv().ok()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Accept clients until the timer drops its `ControlSocket`. The listener is
+/// non-blocking so `stop` is noticed without another client connecting.
+#[cfg(unix)]
+fn accept_clients(listener: UnixListener, sender: Sender<ControlRequest>, stop: &AtomicBool) {
+    while !stop.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let sender = sender.clone();
+                thread::spawn(move || serve_client(stream, sender));
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Answer each line from one client with a line of JSON, until it hangs up.
+#[cfg(unix)]
+fn serve_client(stream: UnixStream, sender: Sender<ControlRequest>) {
+    // Some platforms hand out accepted streams in the listener's non-blocking mode
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match ControlCommand::parse(&line) {
+            Ok(command) => {
+                let (reply, answer) = mpsc::channel();
+                if sender.send(ControlRequest { command, reply }).is_err() {
+                    error_reply("the timer is no longer running")
+                } else {
+                    answer
+                        .recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| error_reply("the timer did not answer"))
+                }
+            }
+            Err(e) => error_reply(&e),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_without_arguments_parse() {
+        assert_eq!(ControlCommand::parse("status"), Ok(ControlCommand::Status));
+        assert_eq!(ControlCommand::parse("pause"), Ok(ControlCommand::Pause));
+        assert_eq!(
+            ControlCommand::parse(" resume\n"),
+            Ok(ControlCommand::Resume)
+        );
+        assert_eq!(ControlCommand::parse("quit"), Ok(ControlCommand::Quit));
+    }
+
+    #[test]
+    fn add_takes_seconds_or_a_duration() {
+        assert_eq!(
+            ControlCommand::parse("add 60"),
+            Ok(ControlCommand::Add(Duration::from_secs(60)))
+        );
+        assert_eq!(
+            ControlCommand::parse("add 5m"),
+            Ok(ControlCommand::Add(Duration::from_secs(300)))
+        );
+    }
+
+    #[test]
+    fn malformed_commands_are_rejected() {
+        assert_eq!(
+            ControlCommand::parse("add"),
+            Err("add needs an amount, e.g. 'add 60'".to_string())
+        );
+        assert_eq!(
+            ControlCommand::parse("pause x"),
+            Err("pause takes no argument".to_string())
+        );
+        assert_eq!(
+            ControlCommand::parse("add 1 2"),
+            Err("too many arguments in 'add 1 2'".to_string())
+        );
+        assert_eq!(
+            ControlCommand::parse("stop"),
+            Err("unknown command 'stop'".to_string())
+        );
+        assert!(ControlCommand::parse("add soon").is_err());
+    }
+
+    #[test]
+    fn replies_reach_the_client_as_json_lines() {
+        let path =
+            std::env::temp_dir().join(format!("tempus-control-test-{}.sock", std::process::id()));
+        let socket = ControlSocket::bind(&path).unwrap();
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let mut stream = UnixStream::connect(client_path).unwrap();
+            stream.write_all(b"status\nbogus\n").unwrap();
+            BufReader::new(stream)
+                .lines()
+                .take(2)
+                .collect::<io::Result<Vec<String>>>()
+                .unwrap()
+        });
+
+        let status =
+            json!({ "name": "Work", "remaining_secs": 60, "percent": 50.0, "paused": false });
+        let request = loop {
+            if let Some(request) = socket.try_recv() {
+                break request;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(request.command, ControlCommand::Status);
+        request.reply(status.to_string());
+
+        let replies = client.join().unwrap();
+        let parse = |line: &str| serde_json::from_str::<serde_json::Value>(line).unwrap();
+        assert_eq!(parse(&replies[0]), status);
+        assert_eq!(
+            parse(&replies[1]),
+            json!({ "error": "unknown command 'bogus'" })
+        );
+
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
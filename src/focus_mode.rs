@@ -1,23 +1,34 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
 use colorgrad;
 use colorgrad::Gradient;
-use crossterm::{
-    event::{self, Event, KeyCode},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::{
-    Terminal,
+    Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Margin},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph},
 };
-use std::io::stdout;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::io::{Write, stdout};
 use std::time::{Duration, Instant};
 
-use crate::utils::{format_simple_duration, send_notification, should_use_color};
-use crate::{ProgressBarTheme, Result};
+use crate::config::load_config;
+use crate::history::{HistoryEntry, log_completion};
+use crate::progress::{
+    ClockFormat, TerminalGuard, TimerOptions, TimerOutcome, check_interrupted, progress_stream,
+    run_timer, running_time, say_completion, set_interrupt_handler,
+};
+use crate::utils::{
+    completion_alert, completion_message, expand_name, flash_screen, format_simple_duration,
+    rgb_to_ansi256, send_notification, send_remaining_notification, should_use_color,
+    sleep_unless_key_pressed, supports_truecolor,
+};
+use crate::webhook::webhook_completion;
+use crate::{ProgressBarTheme, Result, TempusError};
 
 static BIG_DIGITS: [&[&str]; 11] = [
     &[" ███ ", "█   █", "█   █", "█   █", " ███ "], // 0
@@ -33,8 +44,88 @@ static BIG_DIGITS: [&[&str]; 11] = [
     &["     ", "  ░  ", "     ", "  ░  ", "     "], // :
 ];
 
-pub fn render_big_time(time: &str) -> Vec<String> {
-    let mut lines = vec![String::new(); 5];
+/// Seven-segment style digits using only ASCII, for terminals without
+/// Unicode block support.
+static ASCII_BIG_DIGITS: [&[&str]; 11] = [
+    &[" --- ", "|   |", "|   |", "|   |", " --- "], // 0
+    &["     ", "    |", "    |", "    |", "     "], // 1
+    &[" --- ", "    |", " --- ", "|    ", " --- "], // 2
+    &[" --- ", "    |", " --- ", "    |", " --- "], // 3
+    &["     ", "|   |", " --- ", "    |", "     "], // 4
+    &[" --- ", "|    ", " --- ", "    |", " --- "], // 5
+    &[" --- ", "|    ", " --- ", "|   |", " --- "], // 6
+    &[" --- ", "    |", "    |", "    |", "     "], // 7
+    &[" --- ", "|   |", " --- ", "|   |", " --- "], // 8
+    &[" --- ", "|   |", " --- ", "    |", " --- "], // 9
+    &["     ", "  o  ", "     ", "  o  ", "     "], // :
+];
+
+/// Digits drawn with single box-drawing lines.
+static THIN_BIG_DIGITS: [&[&str]; 11] = [
+    &["┌───┐", "│   │", "│   │", "│   │", "└───┘"], // 0
+    &["  ╷  ", "  │  ", "  │  ", "  │  ", "  ╵  "], // 1
+    &["╶───┐", "    │", "┌───┘", "│    ", "└───╴"], // 2
+    &["╶───┐", "    │", " ───┤", "    │", "╶───┘"], // 3
+    &["╷   ╷", "│   │", "└───┤", "    │", "    ╵"], // 4
+    &["┌───╴", "│    ", "└───┐", "    │", "╶───┘"], // 5
+    &["┌───╴", "│    ", "├───┐", "│   │", "└───┘"], // 6
+    &["╶───┐", "    │", "    │", "    │", "    ╵"], // 7
+    &["┌───┐", "│   │", "├───┤", "│   │", "└───┘"], // 8
+    &["┌───┐", "│   │", "└───┤", "    │", "╶───┘"], // 9
+    &["     ", "  ·  ", "     ", "  ·  ", "     "], // :
+];
+
+/// Taller digits in the style of banner(1). Already plain ASCII, so
+/// `--ascii` leaves them alone.
+#[rustfmt::skip]
+static BANNER_BIG_DIGITS: [&[&str]; 11] = [
+    &["  ###  ", " #   # ", "#     #", "#     #", "#     #", " #   # ", "  ###  "], // 0
+    &["   #   ", "  ##   ", " # #   ", "   #   ", "   #   ", "   #   ", " ##### "], // 1
+    &[" ##### ", "#     #", "      #", " ##### ", "#      ", "#      ", "#######"], // 2
+    &[" ##### ", "#     #", "      #", " ##### ", "      #", "#     #", " ##### "], // 3
+    &["#      ", "#    # ", "#    # ", "#    # ", "#######", "     # ", "     # "], // 4
+    &["#######", "#      ", "#      ", "###### ", "      #", "#     #", " ##### "], // 5
+    &[" ##### ", "#     #", "#      ", "###### ", "#     #", "#     #", " ##### "], // 6
+    &["#######", "#    # ", "    #  ", "   #   ", "  #    ", "  #    ", "  #    "], // 7
+    &[" ##### ", "#     #", "#     #", " ##### ", "#     #", "#     #", " ##### "], // 8
+    &[" ##### ", "#     #", "#     #", " ######", "      #", "#     #", " ##### "], // 9
+    &["       ", "       ", "   #   ", "       ", "   #   ", "       ", "       "], // :
+];
+
+/// Digit font for the big clock, chosen with `--font`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BigFont {
+    /// Solid block digits.
+    #[default]
+    Block,
+    /// Taller `#` digits.
+    Banner,
+    /// Single box-drawing lines.
+    Thin,
+}
+
+impl BigFont {
+    /// The digit table for this font. `--ascii` swaps the Unicode fonts for
+    /// the seven-segment ASCII one.
+    fn digits(self, ascii: bool) -> &'static [&'static [&'static str]; 11] {
+        match self {
+            BigFont::Banner => &BANNER_BIG_DIGITS,
+            _ if ascii => &ASCII_BIG_DIGITS,
+            BigFont::Block => &BIG_DIGITS,
+            BigFont::Thin => &THIN_BIG_DIGITS,
+        }
+    }
+}
+
+/// Render `time` (digits and colons) as rows of big digits, with each cell
+/// repeated `scale` times across and down. Every glyph is padded to the width
+/// of the font's `0`, so all rows line up.
+pub(crate) fn render_big_time(time: &str, font: BigFont, ascii: bool, scale: u16) -> Vec<String> {
+    let digits = font.digits(ascii);
+    let width = digits[0][0].chars().count();
+    let scale = scale.max(1) as usize;
+
+    let mut lines = vec![String::new(); digits[0].len()];
     for ch in time.chars() {
         let idx = match ch {
             '0' => 0,
@@ -50,72 +141,464 @@ pub fn render_big_time(time: &str) -> Vec<String> {
             ':' => 10,
             _ => 10,
         };
-        for (i, l) in BIG_DIGITS[idx].iter().enumerate() {
-            lines[i].push_str(l);
-            lines[i].push(' ');
+        for (i, l) in digits[idx].iter().enumerate() {
+            let glyph = format!("{:<width$} ", l);
+            lines[i].extend(glyph.chars().flat_map(|c| std::iter::repeat_n(c, scale)));
         }
     }
     lines
+        .into_iter()
+        .flat_map(|line| std::iter::repeat_n(line, scale))
+        .collect()
+}
+
+/// Largest factor the big clock scales its digits up by on a big terminal.
+const MAX_DIGIT_SCALE: u16 = 3;
+
+/// The font and scale to draw `time` at so it fills as much of a `width` by
+/// `height` area as fits. The chosen font is scaled up by whole steps when
+/// there's room; when even its normal size is too tall, the banner font gives
+/// way to the shorter block one. Nothing fitting at all comes back as the
+/// smallest option, which the caller is left to fall back from.
+pub(crate) fn fit_big_font(
+    time: &str,
+    font: BigFont,
+    ascii: bool,
+    width: u16,
+    height: u16,
+) -> (BigFont, u16) {
+    let candidates: &[BigFont] = if font == BigFont::Banner {
+        &[BigFont::Banner, BigFont::Block]
+    } else {
+        &[font]
+    };
+    let glyphs = time.chars().count();
+    for &candidate in candidates {
+        let digits = candidate.digits(ascii);
+        let rows = digits[0].len();
+        // Each glyph is followed by a blank column
+        let columns = glyphs * (digits[0][0].chars().count() + 1);
+        let fits = |scale: u16| {
+            rows * scale as usize <= height as usize && columns * scale as usize <= width as usize
+        };
+        if let Some(scale) = (1..=MAX_DIGIT_SCALE).rev().find(|&scale| fits(scale)) {
+            return (candidate, scale);
+        }
+    }
+    (candidates[candidates.len() - 1], 1)
+}
+
+/// Cells kept free around the focus mode and big clock boxes.
+pub(crate) const SCREEN_MARGIN: u16 = 2;
+
+/// Rows inside the focus mode box: name, progress bar, time, stats and controls.
+const FOCUS_CONTENT_ROWS: u16 = 5;
+
+/// Narrowest the focus mode box gets before the progress bar is too short to
+/// read.
+const FOCUS_MIN_WIDTH: u16 = 20;
+
+/// How much of the focus mode or big clock screen fits in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScreenLayout {
+    /// The bordered box with everything in it.
+    Full,
+    /// Just the name and the time left, for terminals too small for the box.
+    Minimal,
+}
+
+/// Pick the layout for `area`: `Full` when a bordered box with `content_width`
+/// by `content_height` cells inside fits within the screen margin, `Minimal`
+/// otherwise.
+pub(crate) fn screen_layout(area: Rect, content_width: u16, content_height: u16) -> ScreenLayout {
+    let needed = |content: u16| content.saturating_add(2 + 2 * SCREEN_MARGIN);
+    if area.width >= needed(content_width) && area.height >= needed(content_height) {
+        ScreenLayout::Full
+    } else {
+        ScreenLayout::Minimal
+    }
+}
+
+/// The minimal layout: `name` over `status`, centered in `area`, or both on
+/// one line when there is only room for one.
+pub(crate) fn render_minimal(f: &mut Frame, area: Rect, name: &str, status: Span) {
+    let name = Span::styled(
+        name.to_string(),
+        Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD),
+    );
+    let lines = if area.height >= 2 {
+        vec![Line::from(name), Line::from(status)]
+    } else {
+        vec![Line::from(vec![name, Span::raw(" "), status])]
+    };
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let area = Rect {
+        y: area.y + top,
+        height: area.height - top,
+        ..area
+    };
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
+
+/// Something a key press does in focus mode. Esc always quits and Ctrl-C
+/// always interrupts, whatever the bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Pause,
+    Add,
+    Sub,
+    StepDown,
+    StepUp,
+    Restart,
+    Notify,
+    NotifyDown,
+    NotifyUp,
+    Quit,
+}
+
+impl Action {
+    const ALL: [Action; 10] = [
+        Action::Pause,
+        Action::Add,
+        Action::Sub,
+        Action::StepDown,
+        Action::StepUp,
+        Action::Restart,
+        Action::Notify,
+        Action::NotifyDown,
+        Action::NotifyUp,
+        Action::Quit,
+    ];
+
+    /// The action's name in the `[keys]` config table.
+    fn name(self) -> &'static str {
+        match self {
+            Action::Pause => "pause",
+            Action::Add => "add",
+            Action::Sub => "sub",
+            Action::StepDown => "step_down",
+            Action::StepUp => "step_up",
+            Action::Restart => "restart",
+            Action::Notify => "notify",
+            Action::NotifyDown => "notify_down",
+            Action::NotifyUp => "notify_up",
+            Action::Quit => "quit",
+        }
+    }
+
+    fn default_key(self) -> char {
+        match self {
+            Action::Pause => 'p',
+            Action::Add => '+',
+            Action::Sub => '-',
+            Action::StepDown => '[',
+            Action::StepUp => ']',
+            Action::Restart => 'r',
+            Action::Notify => 'n',
+            Action::NotifyDown => '<',
+            Action::NotifyUp => '>',
+            Action::Quit => 'q',
+        }
+    }
+}
+
+/// The key bound to each focus mode action, indexed like `Action::ALL`.
+pub struct KeyMap {
+    keys: [char; Action::ALL.len()],
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap {
+            keys: Action::ALL.map(Action::default_key),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Apply the `[keys]` config table over the defaults, warning on stderr
+    /// about unknown actions and values that aren't a single character.
+    pub fn from_config(bindings: &BTreeMap<String, String>) -> Self {
+        let mut keymap = KeyMap::default();
+        for (name, key) in bindings {
+            let Some(index) = Action::ALL.iter().position(|action| action.name() == name) else {
+                eprintln!("Warning: ignoring unknown action '{}' in [keys]", name);
+                continue;
+            };
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => keymap.keys[index] = c,
+                _ => eprintln!(
+                    "Warning: [keys] {} must be a single character, keeping '{}'",
+                    name, keymap.keys[index]
+                ),
+            }
+        }
+        keymap
+    }
+
+    fn key(&self, action: Action) -> char {
+        // `Action` is declared in the same order as `Action::ALL`
+        self.keys[action as usize]
+    }
+
+    /// The action bound to `c`. If several actions share a key, the first
+    /// one in `Action::ALL` wins.
+    fn action(&self, c: char) -> Option<Action> {
+        self.keys
+            .iter()
+            .position(|&key| key == c)
+            .map(|index| Action::ALL[index])
+    }
+
+    /// The controls footer, naming the bound keys. Hints that don't fit in
+    /// `width` columns are dropped, least important first.
+    fn controls_text(&self, step: Duration, width: usize) -> String {
+        // In display order, each with its rank: lower ranks are kept longest
+        let hints = [
+            (0, format!("{}: pause", self.key(Action::Pause))),
+            (
+                2,
+                format!(
+                    "{}/{}/↑/↓: ±{}",
+                    self.key(Action::Add),
+                    self.key(Action::Sub),
+                    format_simple_duration(step)
+                ),
+            ),
+            (
+                5,
+                format!(
+                    "PgUp/PgDn: ±{}",
+                    format_simple_duration(step * COARSE_STEP_FACTOR)
+                ),
+            ),
+            (
+                3,
+                format!(
+                    "{}/{}: step",
+                    self.key(Action::StepDown),
+                    self.key(Action::StepUp)
+                ),
+            ),
+            (4, format!("{}: restart", self.key(Action::Restart))),
+            (6, format!("{}: notif", self.key(Action::Notify))),
+            (
+                7,
+                format!(
+                    "{}/{}: notif -/+10s",
+                    self.key(Action::NotifyDown),
+                    self.key(Action::NotifyUp)
+                ),
+            ),
+            (1, format!("{}/ESC: quit", self.key(Action::Quit))),
+        ];
+
+        let mut by_rank: Vec<usize> = (0..hints.len()).collect();
+        by_rank.sort_by_key(|&index| hints[index].0);
+        let mut kept = vec![false; hints.len()];
+        let mut used = 0;
+        for index in by_rank {
+            let separator = if used == 0 { 0 } else { " | ".len() };
+            let needed = separator + hints[index].1.chars().count();
+            if used + needed > width {
+                break;
+            }
+            kept[index] = true;
+            used += needed;
+        }
+
+        hints
+            .into_iter()
+            .zip(kept)
+            .filter(|(_, kept)| *kept)
+            .map(|((_, hint), _)| hint)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// Amounts `+` and `-` can add or remove, cycled with `[` and `]`.
+const TIME_STEPS: [Duration; 5] = [
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+];
+
+/// Lowest threshold for the focus mode's low-time warning.
+const MIN_NOTIFY_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// How many steps Shift+Up/Down and PageUp/PageDown add or remove at once.
+const COARSE_STEP_FACTOR: u32 = 5;
+
+/// `TIME_STEPS` with `step` (from `--step`) slotted in if it isn't one of
+/// them, and the index of `step` in the result.
+fn time_steps(step: Duration) -> (Vec<Duration>, usize) {
+    let mut steps = TIME_STEPS.to_vec();
+    let index = match steps.binary_search(&step) {
+        Ok(index) => index,
+        Err(index) => {
+            steps.insert(index, step);
+            index
+        }
+    };
+    (steps, index)
+}
+
+/// The color `theme` gives a full-screen display at `progress` (0.0 to 1.0),
+/// sampling `gradient` for the gradient-based themes.
+pub fn progress_color(
+    theme: &ProgressBarTheme,
+    gradient: &colorgrad::LinearGradient,
+    progress: f64,
+) -> Color {
+    match theme {
+        ProgressBarTheme::Plain => Color::White,
+        ProgressBarTheme::Gradient | ProgressBarTheme::Custom(_) => {
+            let [r, g, b, _] = gradient.at(progress as f32).to_rgba8();
+            if supports_truecolor() {
+                Color::Rgb(r, g, b)
+            } else {
+                Color::Indexed(rgb_to_ansi256(r, g, b))
+            }
+        }
+        ProgressBarTheme::Color => {
+            // This is the old "Gradient" theme behavior
+            if progress < 0.33 {
+                Color::Green
+            } else if progress < 0.66 {
+                Color::Yellow
+            } else {
+                Color::Red
+            }
+        }
+        ProgressBarTheme::Rainbow => Color::Cyan,
+        ProgressBarTheme::Pulse => Color::Cyan,
+    }
+}
+
+/// Time between animation frames of the pulse and rainbow themes.
+pub const ANIMATION_FRAME: Duration = Duration::from_millis(100);
+
+/// Pulse theme color at animation `frame`: the brightness rises and falls
+/// over 20 frames, i.e. every two seconds.
+pub fn pulse_color(frame: usize) -> Color {
+    let brightness = ((frame as f64 * 0.05) % 1.0 * PI).sin();
+    if brightness > 0.7 {
+        Color::LightCyan
+    } else if brightness > 0.3 {
+        Color::Cyan
+    } else {
+        Color::Blue
+    }
+}
+
+/// Rainbow theme color at animation `frame`: each of the six colors shows for
+/// five frames before the next takes over.
+pub fn rainbow_color(frame: usize) -> Color {
+    const COLORS: [Color; 6] = [
+        Color::Red,
+        Color::Yellow,
+        Color::Green,
+        Color::Cyan,
+        Color::Blue,
+        Color::Magenta,
+    ];
+    COLORS[(frame / 5) % COLORS.len()]
 }
 
 pub struct FocusModeApp {
     duration: Duration,
     name: String,
     theme: ProgressBarTheme,
+    /// The theme's gradient, built once rather than on every frame.
+    gradient: colorgrad::LinearGradient,
     start_time: Instant,
+    /// Wall-clock time of `start_time`, for the history file.
+    start_datetime: DateTime<Local>,
     paused: bool,
     pause_time: Option<Instant>,
     total_pause_duration: Duration,
+    /// Times the timer has been paused since it started or was restarted.
+    pause_count: u32,
     notify_remaining: bool,
     notify_threshold: Duration,
     notified: bool,
     last_duration: Duration,
+    clock_format: ClockFormat,
+    /// Amounts `+` and `-` can adjust by: `TIME_STEPS` and the `--step`.
+    time_steps: Vec<Duration>,
+    /// Index into `time_steps` of the amount `+` and `-` adjust by.
+    time_step: usize,
 }
 
 impl FocusModeApp {
-    pub fn new(duration: Duration, name: &str, theme: ProgressBarTheme) -> Self {
+    pub fn new(
+        duration: Duration,
+        name: &str,
+        theme: ProgressBarTheme,
+        clock_format: ClockFormat,
+        step: Duration,
+        notify_remaining: bool,
+        notify_threshold: Duration,
+    ) -> Self {
+        let (time_steps, time_step) = time_steps(step);
         Self {
             duration,
             name: name.to_string(),
+            gradient: theme.gradient(),
             theme,
             start_time: Instant::now(),
+            start_datetime: Local::now(),
             paused: false,
             pause_time: None,
             total_pause_duration: Duration::from_secs(0),
-            notify_remaining: false,
-            notify_threshold: Duration::from_secs(60),
+            pause_count: 0,
+            notify_remaining,
+            notify_threshold: notify_threshold.max(MIN_NOTIFY_THRESHOLD),
             notified: false,
             last_duration: duration,
+            clock_format,
+            time_steps,
+            time_step,
         }
     }
 
+    /// Color of the border and bar at `progress`. The pulse and rainbow themes
+    /// animate with the time since the timer started instead.
     fn get_color(&self, progress: f64) -> Color {
         match self.theme {
-            ProgressBarTheme::Plain => Color::White,
-            ProgressBarTheme::Gradient => {
-                let gradient: colorgrad::LinearGradient = colorgrad::GradientBuilder::new()
-                    .colors(&[
-                        colorgrad::Color::new(0.0, 1.0, 0.0, 1.0), // Green
-                        colorgrad::Color::new(1.0, 1.0, 0.0, 1.0), // Yellow
-                        colorgrad::Color::new(1.0, 0.0, 0.0, 1.0), // Red
-                    ])
-                    .build()
-                    .expect("Failed to build gradient");
-                let color = gradient.at(progress as f32).to_rgba8();
-                Color::Rgb(color[0], color[1], color[2])
-            }
-            ProgressBarTheme::Color => {
-                // This is the old "Gradient" theme behavior
-                if progress < 0.33 {
-                    Color::Green
-                } else if progress < 0.66 {
-                    Color::Yellow
-                } else {
-                    Color::Red
-                }
-            }
-            ProgressBarTheme::Rainbow => Color::Cyan,
-            ProgressBarTheme::Pulse => Color::Cyan,
+            ProgressBarTheme::Rainbow => rainbow_color(self.frame()),
+            ProgressBarTheme::Pulse => pulse_color(self.frame()),
+            _ => progress_color(&self.theme, &self.gradient, progress),
+        }
+    }
+
+    /// Current animation frame, counted in `ANIMATION_FRAME`s from the start
+    /// so the speed doesn't depend on how often the screen is redrawn.
+    fn frame(&self) -> usize {
+        (self.start_time.elapsed().as_millis() / ANIMATION_FRAME.as_millis()) as usize
+    }
+
+    /// Today's date and the time of day, in the chosen clock format.
+    fn wall_clock(&self) -> String {
+        let now = Local::now();
+        format!(
+            "{} {}",
+            now.format("%a %Y-%m-%d"),
+            self.clock_format.format(&now)
+        )
+    }
+
+    /// Style for secondary text, left uncolored under the plain theme.
+    fn muted_style(&self) -> Style {
+        match self.theme {
+            ProgressBarTheme::Plain => Style::default(),
+            _ => Style::default().fg(Color::DarkGray),
         }
     }
 
@@ -123,29 +606,42 @@ impl FocusModeApp {
         self.paused = !self.paused;
         if self.paused {
             self.pause_time = Some(Instant::now());
+            self.pause_count += 1;
         } else if let Some(pause_start) = self.pause_time {
             self.total_pause_duration += pause_start.elapsed();
             self.pause_time = None;
         }
     }
 
-    fn add_time(&mut self, amount: i64) {
-        if amount > 0 || self.duration > Duration::from_secs(amount.unsigned_abs()) {
-            if amount > 0 {
-                self.duration += Duration::from_secs(amount as u64);
-            } else {
-                self.duration -= Duration::from_secs(amount.unsigned_abs());
-            }
-        }
+    fn step(&self) -> Duration {
+        self.time_steps[self.time_step]
+    }
+
+    fn adjust_step(&mut self, delta: isize) {
+        self.time_step = self
+            .time_step
+            .saturating_add_signed(delta)
+            .min(self.time_steps.len() - 1);
+    }
+
+    fn add_time(&mut self, amount: Duration) {
+        self.duration += amount;
+    }
+
+    /// Shorten the timer by `amount`, but always leave at least a second on
+    /// the clock so subtracting the whole remaining time (or more) doesn't
+    /// end the timer. A timer already under a second is left alone.
+    fn subtract_time(&mut self, amount: Duration) {
+        let floor = (self.elapsed() + Duration::from_secs(1)).min(self.duration);
+        self.duration = self.duration.saturating_sub(amount).max(floor);
     }
 
     fn elapsed(&self) -> Duration {
-        if self.paused {
-            if let Some(pause_start) = self.pause_time {
-                return pause_start.duration_since(self.start_time) - self.total_pause_duration;
-            }
-        }
-        self.start_time.elapsed() - self.total_pause_duration
+        running_time(
+            self.start_time,
+            self.pause_time.filter(|_| self.paused),
+            self.total_pause_duration,
+        )
     }
 
     fn remaining(&self) -> Duration {
@@ -156,6 +652,16 @@ impl FocusModeApp {
         }
     }
 
+    /// How long is left, flagged when paused. The minimal layout shows only this.
+    fn remaining_text(&self) -> String {
+        let remaining = format_simple_duration(self.remaining());
+        if self.paused {
+            format!("PAUSED - {} remaining", remaining)
+        } else {
+            format!("{} remaining", remaining)
+        }
+    }
+
     fn progress(&self) -> f64 {
         let progress = self.elapsed().as_secs_f64() / self.duration.as_secs_f64();
         progress.min(1.0)
@@ -163,18 +669,58 @@ impl FocusModeApp {
 
     fn restart(&mut self) {
         self.start_time = Instant::now();
+        self.start_datetime = Local::now();
         self.paused = false;
         self.pause_time = None;
         self.total_pause_duration = Duration::from_secs(0);
+        self.pause_count = 0;
         self.notified = false;
         self.duration = self.last_duration;
     }
 
+    /// Time spent paused, including the pause in progress.
+    fn paused_total(&self) -> Duration {
+        match self.pause_time {
+            Some(pause_start) if self.paused => self.total_pause_duration + pause_start.elapsed(),
+            _ => self.total_pause_duration,
+        }
+    }
+
+    /// Session statistics: pauses so far and how long they took, and the
+    /// planned duration, followed by the current one once `+`/`-` changed it.
+    fn stats_text(&self) -> String {
+        let mut text = format!(
+            "paused {}x ({}) | planned {}",
+            self.pause_count,
+            format_simple_duration(self.paused_total()),
+            format_simple_duration(self.last_duration)
+        );
+        if self.duration != self.last_duration {
+            text.push_str(&format!(", now {}", format_simple_duration(self.duration)));
+        }
+        text
+    }
+
     fn toggle_notify_remaining(&mut self) {
         self.notify_remaining = !self.notify_remaining;
         self.notified = false;
     }
 
+    /// Whether the remaining time has just dropped to the warning threshold,
+    /// so the warning should go out now. It goes out once per crossing: time
+    /// added back above the threshold re-arms it, as do a restart and a
+    /// threshold change.
+    fn low_time_reached(&mut self) -> bool {
+        if !self.notify_remaining || self.paused {
+            return false;
+        }
+        if self.remaining() > self.notify_threshold {
+            self.notified = false;
+            return false;
+        }
+        !std::mem::replace(&mut self.notified, true)
+    }
+
     fn adjust_notify_threshold(&mut self, delta_secs: i64) {
         let new = if delta_secs.is_positive() {
             self.notify_threshold + Duration::from_secs(delta_secs as u64)
@@ -182,7 +728,7 @@ impl FocusModeApp {
             self.notify_threshold
                 .saturating_sub(Duration::from_secs((-delta_secs) as u64))
         };
-        self.notify_threshold = new.max(Duration::from_secs(1));
+        self.notify_threshold = new.max(MIN_NOTIFY_THRESHOLD);
         self.notified = false;
     }
 }
@@ -190,10 +736,15 @@ impl FocusModeApp {
 pub fn run_focus_mode(
     duration: Duration,
     name: &str,
-    mut theme: ProgressBarTheme,
-    bell: bool,
-    notify: bool,
-) -> Result<()> {
+    options: &TimerOptions,
+) -> Result<TimerOutcome> {
+    // Completes without drawing, the same way as the regular timer
+    if duration.is_zero() {
+        return run_timer(duration, name, options);
+    }
+
+    let name = &expand_name(name, &Local::now(), options.clock_format);
+    let mut theme = options.theme.clone();
     // If NO_COLOR environment variable is set, override theme to Plain
     if !should_use_color() {
         theme = ProgressBarTheme::Plain;
@@ -202,44 +753,83 @@ pub fn run_focus_mode(
         yansi::enable();
     }
 
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    // Load the bindings before entering the alternate screen so any config
+    // warnings stay visible
+    let keys = KeyMap::from_config(&load_config().keys);
+
+    set_interrupt_handler()?;
+    let terminal_guard = TerminalGuard::alternate_screen()?;
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = FocusModeApp::new(duration, name, theme);
+    let mut app = FocusModeApp::new(
+        duration,
+        name,
+        theme,
+        options.clock_format,
+        options.step,
+        options.focus_notify,
+        options.focus_notify_at,
+    );
 
     let tick_rate = Duration::from_millis(100);
-    let res = run_app(&mut terminal, &mut app, tick_rate, bell, notify);
+    let res = run_app(&mut terminal, &mut app, &keys, tick_rate, options);
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    // Restore the terminal whether the timer finished, was quit or was
+    // interrupted, before printing anything to the main screen
+    drop(terminal_guard);
 
+    if let Err(TempusError::Interrupted) = res {
+        writeln!(
+            progress_stream(options.progress_to_stderr),
+            "Timer interrupted."
+        )?;
+    }
     res
 }
 
+/// Number of whole `interval`s in `elapsed`. A `--tick` rings when it goes up.
+fn tick_count(elapsed: Duration, interval: Duration) -> u128 {
+    elapsed.as_nanos() / interval.as_nanos()
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut FocusModeApp,
+    keys: &KeyMap,
     tick_rate: Duration,
-    bell: bool,
-    notify: bool,
-) -> Result<()> {
+    options: &TimerOptions,
+) -> Result<TimerOutcome> {
     let mut last_tick = Instant::now();
+    let mut ticks = 0;
 
     loop {
+        check_interrupted()?;
+
         terminal.draw(|f| {
             let size = f.area();
 
+            if screen_layout(size, FOCUS_MIN_WIDTH, FOCUS_CONTENT_ROWS) == ScreenLayout::Minimal {
+                let color = if app.paused {
+                    Color::Yellow
+                } else {
+                    app.get_color(app.progress())
+                };
+                let status = Span::styled(
+                    app.remaining_text(),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                );
+                render_minimal(f, size, &app.name, status);
+                return;
+            }
+
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .margin(2)
+                .margin(SCREEN_MARGIN)
                 .constraints(
                     [
                         Constraint::Percentage(40),
-                        Constraint::Length(7),
+                        Constraint::Length(8),
                         Constraint::Percentage(40),
                     ]
                     .as_ref(),
@@ -250,7 +840,12 @@ fn run_app<B: ratatui::backend::Backend>(
 
             let progress = app.progress();
 
-            let border_color = if app.notify_remaining && app.remaining() <= app.notify_threshold && !app.paused { Color::Red } else { app.get_color(progress) };
+            let border_color =
+                if app.notify_remaining && app.remaining() <= app.notify_threshold && !app.paused {
+                    Color::Red
+                } else {
+                    app.get_color(progress)
+                };
 
             let block = Block::default()
                 .borders(Borders::ALL)
@@ -260,7 +855,11 @@ fn run_app<B: ratatui::backend::Backend>(
                     Style::default()
                         .fg(Color::White)
                         .add_modifier(Modifier::BOLD),
-                ));
+                ))
+                .title(
+                    Line::styled(format!(" {} ", app.wall_clock()), app.muted_style())
+                        .right_aligned(),
+                );
 
             f.render_widget(block.clone(), timer_area);
 
@@ -272,10 +871,11 @@ fn run_app<B: ratatui::backend::Backend>(
                 .direction(Direction::Vertical)
                 .constraints(
                     [
-                        Constraint::Length(1),    // name
-                        Constraint::Length(1),    // progress bar
-                        Constraint::Length(1),    // time text
-                        Constraint::Length(1),    // controls
+                        Constraint::Length(1), // name
+                        Constraint::Length(1), // progress bar
+                        Constraint::Length(1), // time text
+                        Constraint::Length(1), // stats
+                        Constraint::Length(1), // controls
                     ]
                     .as_ref(),
                 )
@@ -303,7 +903,10 @@ fn run_app<B: ratatui::backend::Backend>(
                     let c = percent_text.chars().nth(i - percent_pos).unwrap_or(' ');
                     // If the percent text is over the filled part, use black fg, else bar color fg
                     let style = if i < filled {
-                        Style::default().fg(Color::Black).bg(bar_color).add_modifier(Modifier::BOLD)
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(bar_color)
+                            .add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(bar_color).add_modifier(Modifier::BOLD)
                     };
@@ -316,17 +919,18 @@ fn run_app<B: ratatui::backend::Backend>(
                     bar_spans.push(Span::raw(" "));
                 }
             }
-            let bar_paragraph = Paragraph::new(Text::from(vec![Line::from(bar_spans)]))
-                .alignment(Alignment::Left);
+            let bar_paragraph =
+                Paragraph::new(Text::from(vec![Line::from(bar_spans)])).alignment(Alignment::Left);
             f.render_widget(bar_paragraph, inner_chunks[1]);
 
             let mut time_text = if app.paused {
+                app.remaining_text()
+            } else {
                 format!(
-                    "PAUSED - {} remaining",
-                    format_simple_duration(app.remaining())
+                    "{} remaining | ends at {}",
+                    format_simple_duration(app.remaining()),
+                    app.clock_format.format_end(app.remaining())
                 )
-            } else {
-                format!("{} remaining", format_simple_duration(app.remaining()))
             };
 
             if app.notify_remaining {
@@ -335,44 +939,72 @@ fn run_app<B: ratatui::backend::Backend>(
 
             let time_paragraph = Paragraph::new(time_text)
                 .alignment(Alignment::Center)
-                .style(Style::default().fg(if app.paused {
-                    Color::Yellow
-                } else {
-                    Color::White
-                }).add_modifier(Modifier::BOLD));
+                .style(
+                    Style::default()
+                        .fg(if app.paused {
+                            Color::Yellow
+                        } else {
+                            Color::White
+                        })
+                        .add_modifier(Modifier::BOLD),
+                );
             f.render_widget(time_paragraph, inner_chunks[2]);
 
-            let controls_text = "p: pause | +: add 1m | -: subtract 1m | r: restart | n: notif | <: -10s notif | >: +10s notif | q/ESC: quit";
-            let controls_paragraph = Paragraph::new(controls_text)
+            let stats_paragraph = Paragraph::new(app.stats_text())
                 .alignment(Alignment::Center)
-                .style(Style::default().fg(Color::DarkGray));
-            f.render_widget(controls_paragraph, inner_chunks[3]);
+                .style(Style::default().fg(Color::Gray));
+            f.render_widget(stats_paragraph, inner_chunks[3]);
+
+            let controls_paragraph =
+                Paragraph::new(keys.controls_text(app.step(), inner_area.width as usize))
+                    .alignment(Alignment::Center)
+                    .style(Style::default().fg(Color::DarkGray));
+            f.render_widget(controls_paragraph, inner_chunks[4]);
         })?;
 
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
-        if app.notify_remaining
-            && !app.notified
-            && app.remaining() <= app.notify_threshold
-            && !app.paused
+        if app.low_time_reached()
+            && options.notify
+            && let Err(e) =
+                send_remaining_notification(&app.name, app.remaining(), &options.notification)
         {
-            app.notified = true;
+            eprintln!("Warning: {}", e);
         }
 
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
                 match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Char('p') => app.toggle_pause(),
-                    KeyCode::Char('+') => app.add_time(60),
-                    KeyCode::Char('-') => app.add_time(-60),
-                    KeyCode::Char('r') => app.restart(),
-                    KeyCode::Char('n') => app.toggle_notify_remaining(),
-                    KeyCode::Char('<') => app.adjust_notify_threshold(-10),
-                    KeyCode::Char('>') => app.adjust_notify_threshold(10),
-                    KeyCode::Esc => return Ok(()),
+                    KeyCode::Esc => return Ok(TimerOutcome::Stopped),
+                    KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                        app.add_time(app.step() * COARSE_STEP_FACTOR)
+                    }
+                    KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                        app.subtract_time(app.step() * COARSE_STEP_FACTOR)
+                    }
+                    KeyCode::PageUp => app.add_time(app.step() * COARSE_STEP_FACTOR),
+                    KeyCode::PageDown => app.subtract_time(app.step() * COARSE_STEP_FACTOR),
+                    KeyCode::Up => app.add_time(app.step()),
+                    KeyCode::Down => app.subtract_time(app.step()),
+                    // Raw mode swallows SIGINT, so Ctrl-C arrives as a key press
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Err(TempusError::Interrupted);
+                    }
+                    KeyCode::Char(c) => match keys.action(c) {
+                        Some(Action::Quit) => return Ok(TimerOutcome::Stopped),
+                        Some(Action::Pause) => app.toggle_pause(),
+                        Some(Action::Add) => app.add_time(app.step()),
+                        Some(Action::Sub) => app.subtract_time(app.step()),
+                        Some(Action::StepDown) => app.adjust_step(-1),
+                        Some(Action::StepUp) => app.adjust_step(1),
+                        Some(Action::Restart) => app.restart(),
+                        Some(Action::Notify) => app.toggle_notify_remaining(),
+                        Some(Action::NotifyDown) => app.adjust_notify_threshold(-10),
+                        Some(Action::NotifyUp) => app.adjust_notify_threshold(10),
+                        None => {}
+                    },
                     _ => {}
                 }
             }
@@ -380,19 +1012,27 @@ fn run_app<B: ratatui::backend::Backend>(
 
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
-        }
 
-        if !app.paused && app.elapsed() >= app.duration {
-            if bell {
-                print!("\x07");
+            // Paused time doesn't count as elapsed, so pausing also silences
+            // the ticks. Ring once however many boundaries have passed since
+            // the last check, and follow a restart back to zero.
+            if let Some(interval) = options.tick {
+                let count = tick_count(app.elapsed(), interval);
+                if count > ticks && app.elapsed() < app.duration {
+                    print!("\x07");
+                    stdout().flush()?;
+                }
+                ticks = count;
             }
+        }
 
+        if !app.paused && app.elapsed() >= app.duration {
             terminal.draw(|f| {
                 let size = f.area();
 
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
-                    .margin(2)
+                    .margin(SCREEN_MARGIN)
                     .constraints(
                         [
                             Constraint::Percentage(40),
@@ -403,9 +1043,18 @@ fn run_app<B: ratatui::backend::Backend>(
                     )
                     .split(size);
 
+                let message = match &options.complete_msg {
+                    Some(template) => completion_message(
+                        template,
+                        &app.name,
+                        app.duration,
+                        app.start_time.elapsed(),
+                    ),
+                    None => format!("{} completed!", app.name),
+                };
                 let completion_text = vec![
                     Line::from(Span::styled(
-                        format!("{} completed!", app.name),
+                        message,
                         Style::default()
                             .fg(Color::Green)
                             .add_modifier(Modifier::BOLD),
@@ -427,15 +1076,273 @@ fn run_app<B: ratatui::backend::Backend>(
                 f.render_widget(completion_paragraph, chunks[1]);
             })?;
 
-            if notify {
-                send_notification(&app.name, app.duration)?;
+            let total_elapsed = app.start_time.elapsed();
+            if options.bell.flash {
+                flash_screen(sleep_unless_key_pressed);
+            }
+            completion_alert(
+                &options.bell,
+                options.sound.as_deref(),
+                sleep_unless_key_pressed,
+            );
+
+            if options.notify
+                && let Err(e) = send_notification(&app.name, app.duration, &options.notification)
+            {
+                eprintln!("Warning: {}", e);
             }
+            say_completion(options, &app.name, app.duration, total_elapsed);
+            webhook_completion(options.webhook.as_deref(), &app.name, total_elapsed);
 
-            if event::poll(Duration::from_secs(u64::MAX))? {
-                let _ = event::read()?;
+            log_completion(
+                options.log.as_deref(),
+                // The planned duration, so `+`/`-` edits show up as a
+                // difference between requested and actual time
+                &HistoryEntry::new(
+                    app.start_datetime,
+                    &app.name,
+                    app.last_duration,
+                    total_elapsed,
+                    "focus",
+                ),
+            );
+
+            // Wait for any key, still giving up on a signal
+            while !event::poll(tick_rate)? {
+                check_interrupted()?;
             }
+            let _ = event::read()?;
 
-            return Ok(());
+            return Ok(TimerOutcome::Completed);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(width: u16, height: u16, content_width: u16, content_height: u16) -> ScreenLayout {
+        screen_layout(
+            Rect::new(0, 0, width, height),
+            content_width,
+            content_height,
+        )
+    }
+
+    #[test]
+    fn box_is_used_when_content_fits_inside_margin_and_borders() {
+        // Five rows of content need two borders and a margin of two on each side
+        assert_eq!(layout(80, 24, 20, 5), ScreenLayout::Full);
+        assert_eq!(layout(26, 11, 20, 5), ScreenLayout::Full);
+    }
+
+    #[test]
+    fn short_or_narrow_terminals_fall_back_to_the_minimal_layout() {
+        assert_eq!(layout(80, 10, 20, 5), ScreenLayout::Minimal);
+        assert_eq!(layout(25, 24, 20, 5), ScreenLayout::Minimal);
+        assert_eq!(layout(80, 1, 20, 5), ScreenLayout::Minimal);
+        assert_eq!(layout(0, 0, 20, 5), ScreenLayout::Minimal);
+    }
+
+    #[test]
+    fn big_clock_needs_room_for_its_digits() {
+        // Five rows of MM:SS digits, 30 cells wide, plus the subtitle line
+        assert_eq!(layout(36, 12, 30, 6), ScreenLayout::Full);
+        assert_eq!(layout(35, 12, 30, 6), ScreenLayout::Minimal);
+        assert_eq!(layout(36, 11, 30, 6), ScreenLayout::Minimal);
+    }
+
+    #[test]
+    fn big_digits_scale_with_the_rows_available() {
+        // MM:SS in the block font is 5 rows by 30 columns at its normal size
+        let fit = |height| fit_big_font("00:05", BigFont::Block, false, 200, height);
+        assert_eq!(fit(4), (BigFont::Block, 1));
+        assert_eq!(fit(5), (BigFont::Block, 1));
+        assert_eq!(fit(9), (BigFont::Block, 1));
+        assert_eq!(fit(10), (BigFont::Block, 2));
+        assert_eq!(fit(15), (BigFont::Block, 3));
+        assert_eq!(fit(100), (BigFont::Block, 3));
+    }
+
+    #[test]
+    fn big_digits_scale_no_wider_than_the_columns_available() {
+        assert_eq!(
+            fit_big_font("00:05", BigFont::Thin, false, 59, 40),
+            (BigFont::Thin, 1)
+        );
+        assert_eq!(
+            fit_big_font("00:05", BigFont::Thin, false, 60, 40),
+            (BigFont::Thin, 2)
+        );
+    }
+
+    #[test]
+    fn banner_gives_way_to_block_digits_when_short() {
+        let fit = |height| fit_big_font("00:05", BigFont::Banner, false, 200, height);
+        assert_eq!(fit(7), (BigFont::Banner, 1));
+        assert_eq!(fit(6), (BigFont::Block, 1));
+        assert_eq!(fit(14), (BigFont::Banner, 2));
+    }
+
+    #[test]
+    fn scaled_digits_repeat_every_cell() {
+        let normal = render_big_time("1", BigFont::Block, true, 1);
+        let doubled = render_big_time("1", BigFont::Block, true, 2);
+        assert_eq!(doubled.len(), normal.len() * 2);
+        assert_eq!(doubled[2], doubled[3]);
+        assert_eq!(
+            doubled[2],
+            normal[1].chars().flat_map(|c| [c, c]).collect::<String>()
+        );
+    }
+
+    fn app(duration: Duration) -> FocusModeApp {
+        FocusModeApp::new(
+            duration,
+            "Work",
+            ProgressBarTheme::Plain,
+            ClockFormat::TwentyFourHour,
+            Duration::from_secs(60),
+            false,
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn subtracting_past_zero_leaves_a_second_on_the_clock() {
+        let mut app = app(Duration::from_secs(60));
+        app.subtract_time(Duration::from_secs(600));
+        assert!(app.duration >= Duration::from_secs(1));
+        assert!(app.duration < Duration::from_secs(2));
+        assert!(app.remaining() > Duration::ZERO);
+    }
+
+    #[test]
+    fn subtracting_less_than_the_remaining_time_shortens_the_timer() {
+        let mut app = app(Duration::from_secs(60));
+        app.subtract_time(Duration::from_secs(20));
+        assert_eq!(app.duration, Duration::from_secs(40));
+    }
+
+    #[test]
+    fn timer_under_a_second_is_left_alone() {
+        let mut app = app(Duration::from_millis(500));
+        app.subtract_time(Duration::from_secs(60));
+        assert_eq!(app.duration, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn controls_list_every_hint_when_wide_enough() {
+        assert_eq!(
+            KeyMap::default().controls_text(Duration::from_secs(60), 200),
+            "p: pause | +/-/↑/↓: ±1m 0s | PgUp/PgDn: ±5m 0s | [/]: step | r: restart | n: notif | </>: notif -/+10s | q/ESC: quit"
+        );
+    }
+
+    #[test]
+    fn controls_drop_the_least_important_hints_to_fit() {
+        let keys = KeyMap::default();
+        let step = Duration::from_secs(60);
+        for width in [0, 10, 40, 64, 80, 100] {
+            assert!(keys.controls_text(step, width).chars().count() <= width);
+        }
+        assert_eq!(
+            keys.controls_text(step, 80),
+            "p: pause | +/-/↑/↓: ±1m 0s | [/]: step | r: restart | q/ESC: quit"
+        );
+        assert_eq!(
+            keys.controls_text(step, 40),
+            "p: pause | +/-/↑/↓: ±1m 0s | q/ESC: quit"
+        );
+        assert_eq!(
+            keys.controls_text(step, 64),
+            "p: pause | +/-/↑/↓: ±1m 0s | [/]: step | q/ESC: quit"
+        );
+        assert_eq!(keys.controls_text(step, 10), "p: pause");
+        assert_eq!(keys.controls_text(step, 0), "");
+    }
+
+    #[test]
+    fn colors_come_from_the_gradient_built_at_startup() {
+        let stop = |r, g, b| colorgrad::Color::from_rgba8(r, g, b, 255);
+        let theme = ProgressBarTheme::Custom(crate::themes::CustomTheme {
+            gradient: vec![stop(255, 0, 0), stop(0, 0, 255)],
+            spinner: stop(255, 255, 255),
+        });
+        let mut app = FocusModeApp::new(
+            Duration::from_secs(60),
+            "Work",
+            theme.clone(),
+            ClockFormat::TwentyFourHour,
+            Duration::from_secs(60),
+            false,
+            Duration::from_secs(60),
+        );
+        for progress in [0.0, 1.0] {
+            assert_eq!(
+                app.get_color(progress),
+                progress_color(&theme, &theme.gradient(), progress)
+            );
+        }
+        assert_ne!(app.get_color(0.0), app.get_color(1.0));
+
+        // Frames sample the stored gradient, not a fresh one from the theme
+        app.gradient = colorgrad::GradientBuilder::new()
+            .colors(&[stop(0, 255, 0), stop(0, 255, 0)])
+            .build()
+            .unwrap();
+        let green = progress_color(&theme, &app.gradient, 0.0);
+        assert_eq!(app.get_color(0.0), green);
+        assert_eq!(app.get_color(1.0), green);
+    }
+
+    #[test]
+    fn remaining_time_notifications_keep_their_seeded_settings() {
+        let seeded = |notify_remaining, threshold| {
+            FocusModeApp::new(
+                Duration::from_secs(600),
+                "Work",
+                ProgressBarTheme::Plain,
+                ClockFormat::TwentyFourHour,
+                Duration::from_secs(60),
+                notify_remaining,
+                threshold,
+            )
+        };
+        let app = seeded(true, Duration::from_secs(90));
+        assert!(app.notify_remaining);
+        assert_eq!(app.notify_threshold, Duration::from_secs(90));
+        assert!(!app.notified);
+
+        let app = seeded(false, Duration::ZERO);
+        assert!(!app.notify_remaining);
+        assert_eq!(app.notify_threshold, MIN_NOTIFY_THRESHOLD);
+    }
+
+    #[test]
+    fn adjusting_the_notify_threshold_saturates_and_rearms() {
+        let mut app = app(Duration::from_secs(600));
+        app.notified = true;
+        app.adjust_notify_threshold(10);
+        assert_eq!(app.notify_threshold, Duration::from_secs(70));
+        assert!(!app.notified);
+
+        app.notified = true;
+        app.adjust_notify_threshold(-100);
+        assert_eq!(app.notify_threshold, Duration::from_secs(1));
+        assert!(!app.notified);
+        app.adjust_notify_threshold(-10);
+        assert_eq!(app.notify_threshold, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn elapsed_time_never_goes_below_zero() {
+        let mut app = app(Duration::from_secs(60));
+        app.total_pause_duration = Duration::from_secs(3_600);
+        assert_eq!(app.elapsed(), Duration::ZERO);
+        app.toggle_pause();
+        assert_eq!(app.elapsed(), Duration::ZERO);
+        assert_eq!(app.remaining(), Duration::from_secs(60));
+    }
+}
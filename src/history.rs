@@ -0,0 +1,175 @@
+use chrono::{DateTime, Local};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const HEADER: &str = "timestamp,name,requested_secs,actual_secs,mode";
+
+/// History file used by a bare `--log`: `$XDG_DATA_HOME/tempus/history.csv`,
+/// falling back to `~/.local/share/tempus/history.csv`.
+pub fn default_history_path() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+
+    Some(data_home.join("tempus").join("history.csv"))
+}
+
+/// One completed timer, written as a row of the history CSV.
+pub struct HistoryEntry<'a> {
+    /// When the timer started.
+    pub timestamp: DateTime<Local>,
+    pub name: &'a str,
+    pub requested: Duration,
+    pub actual: Duration,
+    /// Display mode the timer ran in: `timer`, `focus` or `big`.
+    pub mode: &'a str,
+}
+
+impl HistoryEntry<'_> {
+    pub fn new<'a>(
+        started: DateTime<Local>,
+        name: &'a str,
+        requested: Duration,
+        actual: Duration,
+        mode: &'a str,
+    ) -> HistoryEntry<'a> {
+        HistoryEntry {
+            timestamp: started,
+            name,
+            requested,
+            actual,
+            mode,
+        }
+    }
+
+    fn to_row(&self) -> String {
+        format!(
+            "{},{},{},{:.3},{}",
+            self.timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            csv_field(self.name),
+            self.requested.as_secs_f64(),
+            self.actual.as_secs_f64(),
+            csv_field(self.mode)
+        )
+    }
+}
+
+/// Quote a field if it contains a delimiter, quote or line break, doubling
+/// any embedded quotes.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Append `entry` to the CSV at `path`, creating parent directories and
+/// writing the header first if the file is new or empty.
+pub fn append_entry(path: &Path, entry: &HistoryEntry) -> io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut contents = String::new();
+    if file.metadata()?.len() == 0 {
+        contents.push_str(HEADER);
+        contents.push('\n');
+    }
+    contents.push_str(&entry.to_row());
+    contents.push('\n');
+    file.write_all(contents.as_bytes())
+}
+
+/// Record a completed timer if `--log` is set. Failing to write the history
+/// only prints a warning, as the timer itself has already finished.
+pub fn log_completion(path: Option<&Path>, entry: &HistoryEntry) {
+    if let Some(path) = path
+        && let Err(e) = append_entry(path, entry)
+    {
+        eprintln!(
+            "Warning: could not write history to {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn entry(name: &str) -> HistoryEntry<'_> {
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        HistoryEntry {
+            timestamp: offset
+                .with_ymd_and_hms(2025, 10, 15, 9, 30, 0)
+                .unwrap()
+                .with_timezone(&Local),
+            name,
+            requested: Duration::from_secs(1500),
+            actual: Duration::from_millis(1_502_250),
+            mode: "focus",
+        }
+    }
+
+    /// Everything after the timestamp column, once that column is checked to
+    /// be the entry's instant; its offset depends on the local zone.
+    fn row_after_timestamp(name: &str) -> String {
+        let row = entry(name).to_row();
+        let (timestamp, rest) = row.split_once(',').unwrap();
+        assert_eq!(
+            DateTime::parse_from_rfc3339(timestamp).unwrap(),
+            entry(name).timestamp
+        );
+        rest.to_string()
+    }
+
+    #[test]
+    fn row_lists_the_fields_in_header_order() {
+        assert_eq!(row_after_timestamp("Work"), "Work,1500,1502.250,focus");
+    }
+
+    #[test]
+    fn names_with_commas_or_quotes_are_quoted() {
+        assert_eq!(
+            row_after_timestamp("Write, then edit"),
+            "\"Write, then edit\",1500,1502.250,focus"
+        );
+        assert_eq!(
+            row_after_timestamp("The \"big\" one"),
+            "\"The \"\"big\"\" one\",1500,1502.250,focus"
+        );
+        assert_eq!(
+            row_after_timestamp("two\nlines"),
+            "\"two\nlines\",1500,1502.250,focus"
+        );
+    }
+
+    #[test]
+    fn header_is_only_written_to_a_new_file() {
+        let dir = std::env::temp_dir().join(format!("tempus-history-test-{}", std::process::id()));
+        let path = dir.join("nested").join("history.csv");
+        let _ = fs::remove_dir_all(&dir);
+
+        append_entry(&path, &entry("First")).unwrap();
+        append_entry(&path, &entry("Second")).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], HEADER);
+        assert!(lines[1].ends_with(",First,1500,1502.250,focus"));
+        assert!(lines[2].ends_with(",Second,1500,1502.250,focus"));
+    }
+}
@@ -0,0 +1,137 @@
+//! Terminal timers, countdowns and the focus mode behind the `tempus` binary.
+//!
+//! The displays take a [`TimerOptions`] and run until the timer completes or
+//! is stopped, drawing to stdout. The parsers accept the same durations and
+//! date/times as the command line:
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! assert_eq!(tempus::parse_duration("1h, 30m").unwrap(), Duration::from_secs(5400));
+//! assert!(tempus::parse_datetime("in 5 minutes").is_ok());
+//! assert!(tempus::parse_datetime("not a date").is_err());
+//! ```
+
+pub mod config;
+mod control;
+pub mod focus_mode;
+pub mod history;
+mod parse;
+pub mod progress;
+pub mod state;
+pub mod themes;
+pub mod utils;
+mod webhook;
+
+pub use focus_mode::run_focus_mode;
+pub use parse::{next_time_of_day, parse_datetime, parse_duration, parse_time_of_day};
+pub use progress::{ProgressBarTheme, TimerOptions, TimerOutcome, run_timer};
+
+use std::io;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TempusError {
+    #[error("Invalid duration format: {0}")]
+    InvalidDuration(String),
+
+    #[error("Invalid date/time format: {0}")]
+    InvalidDateTime(String),
+
+    #[error("Target date/time is in the past")]
+    PastDateTime,
+
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("Ctrl-C error: {0}")]
+    CtrlcError(#[from] ctrlc::Error),
+
+    #[error("Notification error: {0}")]
+    Notification(String),
+
+    #[error("Sound error: {0}")]
+    Sound(String),
+
+    #[error("Webhook error: {0}")]
+    Webhook(String),
+
+    #[error("Speech error: {0}")]
+    Speech(String),
+
+    #[error("Control socket error: {0}")]
+    ControlSocket(String),
+
+    #[error("Either DURATION or --preset must be provided when not using a subcommand")]
+    MissingDuration,
+
+    #[error("Preset file error: {0}")]
+    PresetFile(String),
+
+    #[error("No countdown saved with ID '{0}'")]
+    UnknownPersistId(String),
+
+    #[error("Timer interrupted")]
+    Interrupted,
+}
+
+pub type Result<T> = std::result::Result<T, TempusError>;
+
+/// Exit status for a duration, date/time or argument that could not be used,
+/// matching clap's own usage errors.
+pub const EXIT_USAGE: u8 = 2;
+
+/// Exit status after Ctrl-C, following the shell's 128 + SIGINT convention.
+pub const EXIT_INTERRUPTED: u8 = 130;
+
+impl TempusError {
+    /// The process exit status reported for this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            TempusError::InvalidDuration(_)
+            | TempusError::InvalidDateTime(_)
+            | TempusError::PastDateTime
+            | TempusError::MissingDuration => EXIT_USAGE,
+            TempusError::Interrupted => EXIT_INTERRUPTED,
+            TempusError::IoError(_)
+            | TempusError::CtrlcError(_)
+            | TempusError::Notification(_)
+            | TempusError::Sound(_)
+            | TempusError::Webhook(_)
+            | TempusError::Speech(_)
+            | TempusError::ControlSocket(_)
+            | TempusError::PresetFile(_)
+            | TempusError::UnknownPersistId(_) => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_error_has_its_exit_code() {
+        let message = || "x".to_string();
+        let cases = [
+            (TempusError::InvalidDuration(message()), EXIT_USAGE),
+            (TempusError::InvalidDateTime(message()), EXIT_USAGE),
+            (TempusError::PastDateTime, EXIT_USAGE),
+            (TempusError::MissingDuration, EXIT_USAGE),
+            (TempusError::Interrupted, EXIT_INTERRUPTED),
+            (TempusError::IoError(io::Error::other("x")), 1),
+            (TempusError::CtrlcError(ctrlc::Error::MultipleHandlers), 1),
+            (TempusError::Notification(message()), 1),
+            (TempusError::Sound(message()), 1),
+            (TempusError::Webhook(message()), 1),
+            (TempusError::Speech(message()), 1),
+            (TempusError::ControlSocket(message()), 1),
+            (TempusError::PresetFile(message()), 1),
+            (TempusError::UnknownPersistId(message()), 1),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.exit_code(), code, "{:?}", error);
+        }
+        assert_eq!((EXIT_USAGE, EXIT_INTERRUPTED), (2, 130));
+    }
+}
@@ -1,43 +1,37 @@
-mod focus_mode;
-mod progress;
-mod themes;
-mod utils;
-
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono::{DateTime, Days, Local, NaiveTime, TimeZone};
+use clap::builder::BoolishValueParser;
 use clap::{Parser, Subcommand};
-use humantime::parse_duration;
-use progress::{ProgressBarTheme, run_timer};
-use std::{io, process};
-use themes::parse_theme;
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-enum TempusError {
-    #[error("Invalid duration format: {0}")]
-    InvalidDuration(String),
-
-    #[error("Invalid date/time format: {0}")]
-    InvalidDateTime(String),
-
-    #[error("Target date/time is in the past")]
-    PastDateTime,
-
-    #[error("IO error: {0}")]
-    IoError(#[from] io::Error),
-
-    #[error("Ctrl-C error: {0}")]
-    CtrlcError(#[from] ctrlc::Error),
-}
-
-type Result<T> = std::result::Result<T, TempusError>;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use tempus::config::{self, load_config};
+use tempus::focus_mode::{self, BigFont};
+use tempus::progress::{
+    self, BarChars, BarDirection, BarStyle, ClockFormat, OutputFormat, ProgressBarTheme,
+    ProgressStyle, TimerOptions, TimerOutcome, run_timer,
+};
+use tempus::themes::{self, parse_theme};
+use tempus::utils::{
+    BellOptions, NotificationOptions, Urgency, format_precise_duration, format_simple_duration,
+};
+use tempus::{
+    EXIT_INTERRUPTED, Result, TempusError, history, next_time_of_day, parse_datetime,
+    parse_duration, parse_time_of_day, state,
+};
 
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Start a countdown to a specific date/time (e.g. "2025-12-31 23:59:59")
     Countdown {
-        /// Target date/time (e.g. "2025-12-31 23:59:59", "20:00", etc.)
-        #[arg(value_name = "DATETIME")]
-        datetime: String,
+        /// Target date/time (e.g. "2025-12-31 23:59:59", "20:00", "in 90 minutes", etc.),
+        /// or a preset or duration to count down from now (e.g. "pomodoro", "25m")
+        #[arg(value_name = "DATETIME", required_unless_present = "preset")]
+        datetime: Option<String>,
+        /// Count down from now for the length of a preset
+        #[arg(short = 'p', long, conflicts_with = "datetime")]
+        preset: Option<String>,
         /// Name for the countdown event
         #[arg(short, long, default_value = "Countdown")]
         name: String,
@@ -53,7 +47,90 @@ enum Command {
         /// Show big ASCII art clock mode
         #[arg(long, default_value_t = false)]
         big: bool,
+        /// Start the bar at this date/time instead of now, so it shows the whole window
+        #[arg(long, value_name = "DATETIME")]
+        from: Option<String>,
+        /// Save the target under this ID so `tempus resume ID` can continue it
+        #[arg(long, value_name = "ID", value_parser = state::parse_persist_id)]
+        persist: Option<String>,
+    },
+    /// Count down to a date/time, then ring until a key is pressed
+    Alarm {
+        /// Target date/time (e.g. "07:00", "2025-12-31 23:59:59", "in 8 hours"),
+        /// or a preset or duration to count down from now, as for `countdown`
+        #[arg(value_name = "DATETIME")]
+        datetime: String,
+        /// Name for the alarm
+        #[arg(short, long, default_value = "Alarm")]
+        name: String,
+    },
+    /// Continue a countdown started with --persist, e.g. after a restart
+    Resume {
+        /// ID given to --persist
+        #[arg(value_name = "ID", value_parser = state::parse_persist_id)]
+        id: String,
+        /// Progress bar theme (gradient, rainbow, plain, pulse, color, custom)
+        #[arg(short, long, default_value = "gradient")]
+        theme: String,
+        /// Show big ASCII art clock mode
+        #[arg(long, default_value_t = false)]
+        big: bool,
     },
+    /// Print the time left on a countdown started with --persist and exit, e.g. for tmux
+    Status {
+        /// ID given to --persist
+        #[arg(long, value_name = "ID", value_parser = state::parse_persist_id)]
+        id: String,
+    },
+    /// Alternate work and rest intervals (e.g. --work 40s --rest 20s --rounds 8)
+    Interval {
+        /// Length of each work interval
+        #[arg(long)]
+        work: String,
+        /// Length of the rest between work intervals
+        #[arg(long, default_value = "0s")]
+        rest: String,
+        /// Number of work intervals
+        #[arg(long, default_value_t = 1)]
+        rounds: u32,
+        /// Give this workout a name
+        #[arg(short, long, default_value = "Interval")]
+        name: String,
+    },
+    /// Run Pomodoro work sessions with short breaks and a long break every 4th session
+    Pomodoro {
+        /// Number of work sessions
+        #[arg(long, default_value_t = 4)]
+        rounds: u32,
+        /// Length of a work session (defaults to the pomodoro preset)
+        #[arg(long)]
+        work: Option<String>,
+        /// Length of a short break (defaults to the short-break preset)
+        #[arg(long)]
+        short_break: Option<String>,
+        /// Length of a long break (defaults to the long-break preset)
+        #[arg(long)]
+        long_break: Option<String>,
+    },
+    /// Run several timers at once, one bar each (e.g. 25m:work 5m:break)
+    Multi {
+        /// Timers as DURATION:NAME; the name defaults to the duration
+        #[arg(value_name = "DURATION:NAME", required = true, value_parser = parse_multi_spec)]
+        timers: Vec<(String, Duration)>,
+    },
+    /// Count up from zero until stopped with q, Esc or Ctrl-C
+    Stopwatch {
+        /// Give this stopwatch a name
+        #[arg(short, long, default_value = "Stopwatch")]
+        name: String,
+        /// Progress bar theme (gradient, rainbow, plain, pulse, color, custom)
+        #[arg(short, long, default_value = "gradient")]
+        theme: String,
+    },
+    /// List the available presets (built-in and configured) and their durations
+    ListPresets,
+    /// Preview every progress bar theme
+    ListThemes,
 }
 
 #[derive(Parser, Debug)]
@@ -66,32 +143,79 @@ struct Args {
     #[command(subcommand)]
     command: Option<Command>,
 
-    /// Sleep duration (e.g. 5s, 2m, 1h30m)
-    #[arg(value_name = "DURATION")]
-    duration: Option<String>,
+    /// Sleep duration (e.g. 5s, 2m, 1h30m, 1.5h, 1d6h); several run one after another.
+    /// `-` reads the next line of stdin as the duration
+    #[arg(value_name = "DURATION", num_args = 1..)]
+    duration: Vec<String>,
+
+    /// Run the timer (or chain) N times; 0 or `inf` repeats until interrupted
+    #[arg(long, value_name = "N", default_value = "1", value_parser = parse_repeat)]
+    repeat: Repeat,
+
+    /// Wait until this date/time (e.g. 14:00) before starting the timer
+    #[arg(long, value_name = "DATETIME")]
+    start_at: Option<String>,
+
+    /// Keep repeating the timer (or chain) until this date/time, cutting the last run short
+    #[arg(long, value_name = "DATETIME", conflicts_with = "repeat")]
+    loop_until: Option<String>,
+
+    /// Count down to the next time the clock shows this time (e.g. 09:00), then
+    /// start over for the day after, until stopped
+    #[arg(
+        long,
+        value_name = "TIME",
+        value_parser = parse_time_of_day,
+        conflicts_with_all = ["duration", "preset", "stdin", "repeat", "loop_until", "start_at"]
+    )]
+    until_next: Option<NaiveTime>,
+
+    /// Read the duration from stdin (same as passing `-`)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["duration", "preset"])]
+    stdin: bool,
 
-    /// Give this timer a name
-    #[arg(short, long, default_value = "Timer")]
-    name: String,
+    /// Give this timer a name (repeat to name each chained duration)
+    #[arg(short, long, env = "TEMPUS_NAME")]
+    name: Vec<String>,
 
     /// Show more detailed output
     #[arg(short, long, default_value_t = false)]
     verbose: bool,
 
-    /// Progress bar theme (gradient, rainbow, plain, pulse)
-    #[arg(short, long, default_value = "gradient")]
+    /// Progress bar theme (gradient, rainbow, plain, pulse, color, custom)
+    #[arg(short, long, default_value = "gradient", env = "TEMPUS_THEME")]
     theme: String,
 
     /// Use a preset duration (pomodoro, short-break, long-break, tea, coffee)
     #[arg(short = 'p', long)]
     preset: Option<String>,
 
+    /// TOML file of `name = "duration"` presets, taking precedence over the config file
+    #[arg(long, value_name = "PATH", global = true)]
+    preset_file: Option<PathBuf>,
+
     /// Play bell sound when timer completes
-    #[arg(short = 'b', long, default_value_t = true)]
+    #[arg(
+        short = 'b',
+        long,
+        default_value_t = true,
+        env = "TEMPUS_BELL",
+        value_parser = BoolishValueParser::new()
+    )]
     bell: bool,
 
+    /// Don't ring the bell on completion, whatever `--bell` or TEMPUS_BELL say
+    #[arg(long, global = true)]
+    no_bell: bool,
+
     /// Send a desktop notification when timer completes
-    #[arg(short = 'N', long, default_value_t = false)]
+    #[arg(
+        short = 'N',
+        long,
+        default_value_t = false,
+        env = "TEMPUS_NOTIFY",
+        value_parser = BoolishValueParser::new()
+    )]
     notify: bool,
 
     /// Enable focus mode with full-screen TUI
@@ -102,174 +226,1159 @@ struct Args {
     #[arg(long, default_value_t = false)]
     big: bool,
 
-    /// Use 12-hour time format instead of 24-hour
-    #[arg(long, default_value_t = false)]
+    /// Digit font for the big clock
+    #[arg(long, value_enum, default_value_t = BigFont::Block, global = true)]
+    font: BigFont,
+
+    /// Clock format for the start, end and ETA times
+    #[arg(long, value_enum, default_value_t = ClockFormat::TwentyFourHour, global = true)]
+    clock_format: ClockFormat,
+
+    /// Shorthand for `--clock-format 12h`
+    #[arg(long, default_value_t = false, hide = true, global = true)]
     use_12h: bool,
+
+    /// Custom progress bar characters (e.g. "fill=#,empty=-,left=[,right=]")
+    #[arg(long, value_name = "SPEC", global = true)]
+    chars: Option<BarChars>,
+
+    /// Only use ASCII characters for the bar, spinner and big clock digits
+    #[arg(long, default_value_t = false, global = true)]
+    ascii: bool,
+
+    /// Shell command to run as the notification, with {name} and {duration}
+    /// placeholders (implies --notify)
+    #[arg(long, value_name = "TEMPLATE", global = true)]
+    notify_cmd: Option<String>,
+
+    /// Urgency of desktop notifications
+    #[arg(long, value_enum, default_value_t = Urgency::Normal, global = true)]
+    notify_urgency: Urgency,
+
+    /// How long desktop notifications stay on screen (0s keeps them until dismissed)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    notify_timeout: Option<Duration>,
+
+    /// Also notify when these percentages are reached (e.g. 50,90)
+    #[arg(
+        long,
+        value_name = "PERCENTS",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u8).range(1..100),
+        global = true
+    )]
+    notify_at: Vec<u8>,
+
+    /// How many times to ring the bell on completion
+    #[arg(long, value_name = "N", default_value_t = 1, global = true)]
+    bell_repeat: u32,
+
+    /// Pause between repeated bells (e.g. 500ms, 2s)
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "1s",
+        value_parser = parse_duration,
+        global = true
+    )]
+    bell_interval: Duration,
+
+    /// Flash the screen on completion, as a visual bell
+    #[arg(long, default_value_t = false, global = true)]
+    flash: bool,
+
+    /// What to show after the progress bar
+    #[arg(long, value_enum, default_value_t = ProgressStyle::Percent, global = true)]
+    progress_style: ProgressStyle,
+
+    /// Whether the progress bar fills up or empties as time passes
+    #[arg(long, value_enum, default_value_t = BarDirection::Fill, global = true)]
+    bar_direction: BarDirection,
+
+    /// Glyphs for the progress bar: block (default) or braille, which fills the
+    /// leading cell dot by dot
+    #[arg(long, value_enum, default_value_t = BarStyle::Block, global = true)]
+    bar_style: BarStyle,
+
+    /// Print the resolved duration or countdown target and exit without running
+    #[arg(long, default_value_t = false, global = true)]
+    dry_run: bool,
+
+    /// Leave out the spinner in front of the progress bar
+    #[arg(long, default_value_t = false, global = true)]
+    no_spinner: bool,
+
+    /// Draw the timer on a single line that fits the terminal width
+    #[arg(long, default_value_t = false, global = true)]
+    compact: bool,
+
+    /// Draw the progress on stderr, so stdout only gets the completion line
+    #[arg(long, default_value_t = false, global = true)]
+    progress_to_stderr: bool,
+
+    /// Take commands (status, pause, resume, add SECS, quit) on this Unix socket
+    #[arg(long, value_name = "PATH", global = true)]
+    control_socket: Option<PathBuf>,
+
+    /// Read the completion message aloud with the system's text-to-speech
+    #[arg(long, default_value_t = false, global = true)]
+    say: bool,
+
+    /// URL to POST a JSON message to on completion, with {name} and {duration}
+    /// placeholders (needs the `webhook` feature)
+    #[arg(long, value_name = "URL", global = true)]
+    webhook: Option<String>,
+
+    /// Sound file to play on completion instead of the terminal bell
+    #[arg(long, value_name = "PATH", global = true)]
+    sound: Option<PathBuf>,
+
+    /// Output format: the progress bar, or one JSON status line per second
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    format: OutputFormat,
+
+    /// Keep this file updated with the remaining time and percentage
+    #[arg(long, value_name = "PATH", global = true)]
+    status_file: Option<PathBuf>,
+
+    /// Show the remaining time in the terminal window title
+    #[arg(long, default_value_t = false, global = true)]
+    set_title: bool,
+
+    /// Report progress to the terminal's tab/taskbar (OSC 9;4)
+    #[arg(long, default_value_t = false, global = true)]
+    os_progress: bool,
+
+    /// Append a CSV row to this file when a timer completes
+    /// (bare `--log` uses ~/.local/share/tempus/history.csv)
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true,
+        global = true
+    )]
+    log: Option<Option<PathBuf>>,
+
+    /// Skip the progress display and only print the completion line
+    #[arg(short, long, default_value_t = false, global = true)]
+    quiet: bool,
+
+    /// Turn the header red and ring the bell once when this much time is left
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    warn_at: Option<Duration>,
+
+    /// Amount the focus mode's +/- and Up/Down keys start out adjusting by (PageUp/PageDown: 5x)
+    #[arg(long, value_name = "DURATION", default_value = "1m", value_parser = parse_step, global = true)]
+    step: Duration,
+
+    /// Start focus mode with its low-time warning (toggled with n) turned on
+    #[arg(long, default_value_t = false, global = true)]
+    focus_notify: bool,
+
+    /// Time left at which focus mode's low-time warning starts (adjusted with < and >)
+    #[arg(long, value_name = "DURATION", default_value = "1m", value_parser = parse_duration, global = true)]
+    focus_notify_at: Duration,
+
+    /// Call out the time left whenever it is a multiple of DURATION (spoken with --say, else a beep)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    announce_every: Option<Duration>,
+
+    /// Call out each of the last N seconds, e.g. 3 for "3, 2, 1"
+    #[arg(long, value_name = "N", default_value_t = 0, global = true)]
+    count_in: u64,
+
+    /// Ring the bell every DURATION during a focus session (e.g. 1s, 5m)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    tick: Option<Duration>,
+
+    /// Completion message with {name}, {duration} and {elapsed} placeholders
+    #[arg(long, value_name = "TEMPLATE", global = true)]
+    complete_msg: Option<String>,
 }
 
-fn parse_datetime(datetime: &str) -> Result<DateTime<Local>> {
-    DateTime::parse_from_rfc3339(datetime)
-        .map(|dt| dt.with_timezone(&Local))
-        .or_else(|_| {
-            NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S")
-                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
-                .and_then(|ndt| {
-                    Local.from_local_datetime(&ndt)
-                        .single()
-                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
-                })
-        })
-        .or_else(|_| {
-            NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M")
-                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
-                .and_then(|ndt| {
-                    Local.from_local_datetime(&ndt)
-                        .single()
-                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
-                })
-        })
-        .or_else(|_| {
-            NaiveDate::parse_from_str(datetime, "%Y-%m-%d")
-                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
-                .and_then(|nd| {
-                    nd.and_hms_opt(0, 0, 0)
-                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
-                })
-                .and_then(|ndt| {
-                    Local.from_local_datetime(&ndt)
-                        .single()
-                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
-                })
-        })
-        .or_else(|_| {
-            NaiveTime::parse_from_str(datetime, "%H:%M:%S")
-                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
-                .and_then(|nt| {
-                    let now = Local::now();
-                    let today = now.date_naive();
-                    let ndt = today.and_time(nt);
-                    let dt = Local.from_local_datetime(&ndt)
-                        .single()
-                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))?;
-                    if dt <= now {
-                        let tomorrow = today.succ_opt().ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))?;
-                        let ndt_tomorrow = tomorrow.and_time(nt);
-                        Local.from_local_datetime(&ndt_tomorrow)
-                            .single()
-                            .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
-                    } else {
-                        Ok(dt)
-                    }
-                })
-        })
-        .or_else(|_| {
-            NaiveTime::parse_from_str(datetime, "%H:%M")
-                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
-                .and_then(|nt| {
-                    let now = Local::now();
-                    let today = now.date_naive();
-                    let ndt = today.and_time(nt);
-                    let dt = Local.from_local_datetime(&ndt)
-                        .single()
-                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))?;
-                    if dt <= now {
-                        let tomorrow = today.succ_opt().ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))?;
-                        let ndt_tomorrow = tomorrow.and_time(nt);
-                        Local.from_local_datetime(&ndt_tomorrow)
-                            .single()
-                            .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
-                    } else {
-                        Ok(dt)
-                    }
-                })
+/// How many times `--repeat` runs the timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repeat {
+    Times(u32),
+    /// Until stopped with q or interrupted.
+    Forever,
+}
+
+impl Repeat {
+    /// Whether round `round` (counting from 1) should run.
+    fn runs(self, round: u32) -> bool {
+        match self {
+            Repeat::Times(rounds) => round <= rounds,
+            Repeat::Forever => true,
+        }
+    }
+
+    /// Timer name for round `round`: unchanged for a single run, otherwise
+    /// numbered like "Timer (2/5)", or "Timer (2)" when repeating forever.
+    fn label(self, name: &str, round: u32) -> String {
+        match self {
+            Repeat::Times(1) => name.to_string(),
+            Repeat::Times(rounds) => format!("{} ({}/{})", name, round, rounds),
+            Repeat::Forever => format!("{} ({})", name, round),
+        }
+    }
+}
+
+/// Parse `--repeat`: a number of runs, with `0` or `inf` repeating forever.
+fn parse_repeat(value: &str) -> std::result::Result<Repeat, String> {
+    if value.eq_ignore_ascii_case("inf") {
+        return Ok(Repeat::Forever);
+    }
+    match value.parse::<u32>() {
+        Ok(0) => Ok(Repeat::Forever),
+        Ok(rounds) => Ok(Repeat::Times(rounds)),
+        Err(_) => Err(format!(
+            "expected a number of runs or `inf`, got '{}'",
+            value
+        )),
+    }
+}
+
+/// Parse a `tempus multi` timer given as `DURATION:NAME` into its name and
+/// duration. Without a name, the duration as written is used instead.
+fn parse_multi_spec(spec: &str) -> Result<(String, Duration)> {
+    let (duration, name) = spec.split_once(':').unwrap_or((spec, ""));
+    let name = match name.trim() {
+        "" => duration.trim(),
+        name => name,
+    };
+    Ok((name.to_string(), parse_duration(duration)?))
+}
+
+/// Parse `--step`, which has to be at least a second.
+fn parse_step(step: &str) -> Result<Duration> {
+    let duration = parse_duration(step)?;
+    if duration < Duration::from_secs(1) {
+        return Err(TempusError::InvalidDuration(format!(
+            "{} (the step must be at least 1s)",
+            step
+        )));
+    }
+    Ok(duration)
+}
+
+/// Built-in presets. Presets from the config file are merged over these.
+const PRESETS: &[(&str, &str)] = &[
+    ("coffee", "4m"),
+    ("long-break", "15m"),
+    ("pomodoro", "25m"),
+    ("short-break", "5m"),
+    ("tea", "3m"),
+];
+
+/// Merge the user's configured presets over the built-in ones, user entries winning.
+fn merged_presets(args: &Args) -> Result<BTreeMap<String, String>> {
+    let mut presets: BTreeMap<String, String> = PRESETS
+        .iter()
+        .map(|(name, duration)| (name.to_string(), duration.to_string()))
+        .collect();
+    add_presets(&mut presets, load_config().presets, "the config file");
+    if let Some(path) = &args.preset_file {
+        let overrides = config::load_preset_file(path).map_err(TempusError::PresetFile)?;
+        add_presets(&mut presets, overrides, &path.display().to_string());
+    }
+    Ok(presets)
+}
+
+/// Add user-defined `overrides` over `presets`, replacing built-ins of the same
+/// name. Entries whose duration doesn't parse are skipped with a warning
+/// naming `source`, so one typo doesn't take the other presets down with it.
+fn add_presets(
+    presets: &mut BTreeMap<String, String>,
+    overrides: BTreeMap<String, String>,
+    source: &str,
+) {
+    for (name, duration) in overrides {
+        if parse_duration(&duration).is_err() {
+            eprintln!(
+                "Warning: ignoring preset '{}' in {}: invalid duration '{}'",
+                name, source, duration
+            );
+            continue;
+        }
+        presets.insert(name, duration);
+    }
+}
+
+fn get_duration_from_preset(presets: &BTreeMap<String, String>, preset: &str) -> String {
+    presets
+        .get(preset)
+        .map_or(preset, String::as_str)
+        .to_string()
+}
+
+fn list_presets(presets: &BTreeMap<String, String>) {
+    for name in presets.keys() {
+        println!("{:<12} {}", name, get_duration_from_preset(presets, name));
+    }
+}
+
+/// Bar glyphs to use: explicit `--chars` win, then `--ascii`, then the ones
+/// for `--bar-style`.
+fn bar_chars(args: &Args) -> BarChars {
+    args.chars
+        .unwrap_or_else(|| match (args.ascii, args.bar_style) {
+            (true, _) => BarChars::ascii(),
+            (false, BarStyle::Block) => BarChars::default(),
+            (false, BarStyle::Braille) => BarChars::braille(),
         })
-        .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
 }
 
-fn get_duration_from_preset(preset: &str) -> String {
-    match preset {
-        "pomodoro" => "25m".to_string(),
-        "short-break" => "5m".to_string(),
-        "long-break" => "15m".to_string(),
-        "tea" => "3m".to_string(),
-        "coffee" => "4m".to_string(),
-        custom => custom.to_string(),
+/// `--no-bell` wins over a bell turned on by default, by flag or by TEMPUS_BELL.
+fn bell_options(args: &Args, enabled: bool) -> BellOptions {
+    BellOptions {
+        enabled: enabled && !args.no_bell,
+        flash: args.flash,
+        repeat: args.bell_repeat,
+        interval: args.bell_interval,
+    }
+}
+
+/// Options for the inline timer displays, built from the global flags.
+/// `--notify-cmd` implies `notify`.
+fn timer_options(args: &Args, theme: ProgressBarTheme, bell: bool, notify: bool) -> TimerOptions {
+    TimerOptions {
+        verbose: args.verbose,
+        theme,
+        bell: bell_options(args, bell),
+        notify: notify || args.notify_cmd.is_some(),
+        clock_format: if args.use_12h {
+            ClockFormat::TwelveHour
+        } else {
+            args.clock_format
+        },
+        chars: bar_chars(args),
+        ascii: args.ascii,
+        notification: NotificationOptions {
+            command: args.notify_cmd.clone(),
+            urgency: args.notify_urgency,
+            timeout: args.notify_timeout,
+        },
+        notify_at: args.notify_at.clone(),
+        sound: args.sound.clone(),
+        format: args.format,
+        status_file: args.status_file.clone(),
+        set_title: args.set_title,
+        os_progress: args.os_progress,
+        log: log_path(args),
+        quiet: args.quiet,
+        warn_at: args.warn_at,
+        font: args.font,
+        tick: args.tick.filter(|interval| !interval.is_zero()),
+        step: args.step,
+        focus_notify: args.focus_notify,
+        focus_notify_at: args.focus_notify_at,
+        complete_msg: args.complete_msg.clone(),
+        webhook: args.webhook.clone(),
+        say: args.say,
+        progress_style: args.progress_style,
+        bar_direction: args.bar_direction,
+        spinner: !args.no_spinner,
+        head_start: Duration::ZERO,
+        control_socket: args.control_socket.clone(),
+        announce_every: args.announce_every.filter(|every| !every.is_zero()),
+        count_in: args.count_in,
+        compact: args.compact,
+        progress_to_stderr: args.progress_to_stderr,
+    }
+}
+
+/// Resolve `--log`: an explicit path, or the default history file when the
+/// flag is given bare.
+fn log_path(args: &Args) -> Option<PathBuf> {
+    match &args.log {
+        Some(Some(path)) => Some(path.clone()),
+        Some(None) => {
+            let path = history::default_history_path();
+            if path.is_none() {
+                eprintln!("Warning: cannot locate the default history file, HOME is not set");
+            }
+            path
+        }
+        None => None,
     }
 }
 
-fn handle_countdown(cmd: &Command) -> Result<()> {
+fn handle_countdown(args: &Args, cmd: &Command) -> Result<()> {
     let Command::Countdown {
         datetime,
+        preset,
         name,
         theme,
         bell,
         notify,
         big,
-    } = cmd;
+        from,
+        persist,
+    } = cmd
+    else {
+        unreachable!("handle_countdown called with {:?}", cmd);
+    };
+
+    let target = match (datetime, preset) {
+        (Some(datetime), _) => countdown_target(args, datetime)?,
+        (None, Some(preset)) => relative_target(args, preset)?,
+        (None, None) => unreachable!("clap requires DATETIME or --preset"),
+    };
+    let from = from
+        .as_deref()
+        .map(|from| parse_from_datetime(from, target))
+        .transpose()?;
+    let theme_enum = parse_theme(theme);
+
+    let options = TimerOptions {
+        verbose: false,
+        head_start: head_start(from, Local::now()),
+        ..timer_options(args, theme_enum, *bell, *notify)
+    };
+
+    if args.dry_run {
+        return print_countdown_plan(name, target, options.clock_format);
+    }
 
-    let target = parse_datetime(datetime)?;
-    let now = Local::now();
+    if let Some(id) = persist {
+        let countdown = state::PersistedCountdown {
+            name: name.clone(),
+            target,
+            from,
+        };
+        if let Err(e) = state::save(id, &countdown) {
+            eprintln!("Warning: could not save countdown '{}': {}", id, e);
+        }
+    }
+
+    run_countdown(target, name, *big, &options, persist.as_deref())?;
+    Ok(())
+}
 
-    let duration = (target - now)
+/// Target for `countdown DATETIME` and `alarm DATETIME`. A date or time is tried first, so `20:00`
+/// or `in 90 minutes` keep their meaning even if a preset has the same name;
+/// anything else is read as a preset or a duration to count down from now.
+/// The date/time error is only reported for input that is neither.
+fn countdown_target(args: &Args, datetime: &str) -> Result<DateTime<Local>> {
+    let datetime_err = match parse_datetime(datetime) {
+        Ok(target) => return Ok(target),
+        Err(e) => e,
+    };
+    let presets = merged_presets(args)?;
+    if !presets.contains_key(datetime) && parse_duration(datetime).is_err() {
+        return Err(datetime_err);
+    }
+    relative_target(args, datetime)
+}
+
+/// Now plus the length of `preset`, which may also be a plain duration such
+/// as `25m`, the same as for `--preset`.
+fn relative_target(args: &Args, preset: &str) -> Result<DateTime<Local>> {
+    let duration = get_duration_from_preset(&merged_presets(args)?, preset);
+    parse_duration(&duration)?;
+    parse_datetime(&format!("in {}", duration))
+}
+
+/// Pick up a countdown saved with `--persist`. One whose target has passed
+/// in the meantime is reported as completed straight away.
+fn handle_resume(args: &Args, id: &str, theme: &str, big: bool) -> Result<()> {
+    let countdown =
+        state::load(id)?.ok_or_else(|| TempusError::UnknownPersistId(id.to_string()))?;
+
+    if countdown.target <= Local::now() {
+        println!(
+            "{} completed! (target {} has passed)",
+            countdown.name,
+            countdown.target.format("%Y-%m-%d %H:%M:%S")
+        );
+        state::remove(id)?;
+        return Ok(());
+    }
+
+    let options = TimerOptions {
+        verbose: false,
+        head_start: head_start(countdown.from, Local::now()),
+        ..timer_options(args, parse_theme(theme), args.bell, args.notify)
+    };
+    run_countdown(countdown.target, &countdown.name, big, &options, Some(id))?;
+    Ok(())
+}
+
+/// `tempus status`: one line with the time left on a persisted countdown, or
+/// `done` once its target has passed. Unlike `resume`, the state file is only
+/// read, so this can be polled every second.
+fn handle_status(id: &str) -> Result<()> {
+    println!(
+        "{}",
+        status_line(id, state::load(id)?.as_ref(), Local::now())?
+    );
+    Ok(())
+}
+
+/// What `tempus status` prints at `now` for `countdown`, the one saved as
+/// `id` if there is one.
+fn status_line(
+    id: &str,
+    countdown: Option<&state::PersistedCountdown>,
+    now: DateTime<Local>,
+) -> Result<String> {
+    let countdown = countdown.ok_or_else(|| TempusError::UnknownPersistId(id.to_string()))?;
+    Ok(match (countdown.target - now).to_std() {
+        Ok(remaining) if !remaining.is_zero() => format_simple_duration(remaining),
+        _ => "done".to_string(),
+    })
+}
+
+/// Count down like `countdown`, then keep ringing until acknowledged.
+fn handle_alarm(args: &Args, datetime: &str, name: &str) -> Result<()> {
+    let target = countdown_target(args, datetime)?;
+    let options = TimerOptions {
+        verbose: false,
+        ..timer_options(args, parse_theme(&args.theme), args.bell, args.notify)
+    };
+
+    if args.dry_run {
+        return print_countdown_plan(name, target, options.clock_format);
+    }
+
+    if run_countdown(target, name, false, &options, None)? == TimerOutcome::Completed {
+        progress::ring_until_acknowledged(&options)?;
+    }
+    Ok(())
+}
+
+/// Time left until `target`. Take `now` as late as possible: the target may
+/// have passed since it was parsed, or may have been in the past to begin
+/// with (e.g. an old date). A target of exactly now counts as past too, so
+/// every countdown has something left to count.
+fn time_until(target: DateTime<Local>) -> Result<Duration> {
+    (target - Local::now())
         .to_std()
-        .expect("Duration should be positive");
-    let theme_enum = parse_theme(theme);
+        .ok()
+        .filter(|remaining| !remaining.is_zero())
+        .ok_or(TempusError::PastDateTime)
+}
 
-    if *big {
-        return progress::run_big_clock(duration, name, *bell).map_err(TempusError::IoError);
+/// Parse a countdown's `--from`. A time of day that has already passed today
+/// resolves to tomorrow like any target would, but as a start time it means
+/// today's, so it is moved back a day when that keeps it before the target.
+fn parse_from_datetime(from: &str, target: DateTime<Local>) -> Result<DateTime<Local>> {
+    let mut start = parse_datetime(from)?;
+    if start >= target
+        && start.date_naive() > Local::now().date_naive()
+        && let Some(earlier) = start.checked_sub_days(Days::new(1))
+    {
+        start = earlier;
+    }
+    if start >= target {
+        return Err(TempusError::InvalidDateTime(format!(
+            "{} is not before the target",
+            from
+        )));
     }
+    Ok(start)
+}
+
+/// How much of a countdown started at `from` has passed by `now`. A start in
+/// the future counts as now.
+fn head_start(from: Option<DateTime<Local>>, now: DateTime<Local>) -> Duration {
+    from.and_then(|from| (now - from).to_std().ok())
+        .unwrap_or(Duration::ZERO)
+}
+
+/// `--dry-run` for a countdown: show the target and how far away it is.
+fn print_countdown_plan(
+    name: &str,
+    target: DateTime<Local>,
+    clock_format: ClockFormat,
+) -> Result<()> {
+    let duration = time_until(target)?;
+    println!(
+        "{}: {} {} (in {})",
+        name,
+        target.format("%Y-%m-%d"),
+        clock_format.format(&target),
+        format_simple_duration(duration)
+    );
+    Ok(())
+}
 
-    // For countdown, we'll use default 24h time format since there's no option in the countdown command
-    run_timer(duration, name, false, theme_enum, *bell, *notify, false)
+/// The `--until-next` target following one at `previous`: the same time on
+/// the next day, or the first one still ahead of `now` if the machine slept
+/// through some of them.
+fn next_occurrence<Tz: TimeZone>(
+    previous: DateTime<Tz>,
+    now: DateTime<Tz>,
+    time: NaiveTime,
+) -> Option<DateTime<Tz>> {
+    next_time_of_day(previous.max(now), time)
+}
+
+/// `--until-next`: count down to the next `time`, and each time it is
+/// reached, straight on to the one after. Runs until stopped with `q` or
+/// Ctrl-C.
+fn handle_until_next(args: &Args, time: NaiveTime) -> Result<()> {
+    let invalid = || TempusError::InvalidDateTime(time.to_string());
+    let name = args.name.first().map_or("Countdown", String::as_str);
+    let options = timer_options(args, parse_theme(&args.theme), args.bell, args.notify);
+
+    let mut target = next_time_of_day(Local::now(), time).ok_or_else(invalid)?;
+    if args.dry_run {
+        print_countdown_plan(name, target, options.clock_format)?;
+        println!("Repeated daily until stopped");
+        return Ok(());
+    }
+
+    while run_countdown(target, name, args.big, &options, None)? == TimerOutcome::Completed {
+        target = next_occurrence(target, Local::now(), time).ok_or_else(invalid)?;
+    }
+    Ok(())
+}
+
+/// Count down to `target`, then delete the `--persist` state file if the
+/// countdown ran to the end. Stopping it early keeps the file for `resume`.
+fn run_countdown(
+    target: DateTime<Local>,
+    name: &str,
+    big: bool,
+    options: &TimerOptions,
+    persist: Option<&str>,
+) -> Result<TimerOutcome> {
+    let duration = time_until(target)? + options.head_start;
+
+    let outcome = if big {
+        progress::run_big_clock(duration, name, options)?
+    } else {
+        run_timer(duration, name, options)?
+    };
+
+    if outcome == TimerOutcome::Completed
+        && let Some(id) = persist
+        && let Err(e) = state::remove(id)
+    {
+        eprintln!("Warning: could not remove saved countdown '{}': {}", id, e);
+    }
+    Ok(outcome)
+}
+
+/// Pair each duration with a name: the `--name` at the same position if there
+/// is one, otherwise "Timer" for a single timer or "Timer N" within a chain.
+/// Surplus names are ignored.
+fn timer_segments(durations: &[String], names: &[String]) -> Vec<(String, String)> {
+    durations
+        .iter()
+        .enumerate()
+        .map(|(i, duration)| {
+            let name = names.get(i).cloned().unwrap_or_else(|| {
+                if durations.len() == 1 {
+                    "Timer".to_string()
+                } else {
+                    format!("Timer {}", i + 1)
+                }
+            });
+            (name, duration.clone())
+        })
+        .collect()
+}
+
+/// Read the duration for a `-` argument: the next line of `input`, trimmed.
+/// Each `-` reads its own line, so `printf '25m\n5m\n' | tempus - -` works.
+fn read_duration_line(input: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let line = line.trim();
+    if line.is_empty() {
+        return Err(TempusError::InvalidDuration(
+            "no duration on stdin".to_string(),
+        ));
+    }
+    Ok(line.to_string())
+}
+
+/// Length of the next `--loop-until` run of a `duration` timer starting at
+/// `now`: cut short so it ends at `deadline`, or `None` once the deadline has
+/// been reached.
+fn loop_run_length(
+    duration: Duration,
+    deadline: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Option<Duration> {
+    let left = (deadline - now).to_std().unwrap_or(Duration::ZERO);
+    (!left.is_zero()).then(|| duration.min(left))
+}
+
+/// `--dry-run` line for `--start-at`: when the timer would begin.
+fn print_start_at(start_at: DateTime<Local>, clock_format: ClockFormat) {
+    println!(
+        "Starts at: {} {} (in {})",
+        start_at.format("%Y-%m-%d"),
+        clock_format.format(&start_at),
+        format_simple_duration(progress::delay_until(start_at))
+    );
+}
+
+/// `--dry-run` for timers: one line per timer in the chain, then the total
+/// and how often it repeats.
+fn print_timer_plan(segments: &[(String, Duration)], repeat: Repeat) {
+    for (name, duration) in segments {
+        println!("{}: {}", name, format_simple_duration(*duration));
+    }
+    if segments.len() > 1 {
+        let total: Duration = segments.iter().map(|(_, duration)| *duration).sum();
+        println!("Total: {}", format_simple_duration(total));
+    }
+    match repeat {
+        Repeat::Times(1) => {}
+        Repeat::Times(rounds) => println!("Repeated {} times", rounds),
+        Repeat::Forever => println!("Repeated until stopped"),
+    }
 }
 
 fn handle_timer(args: &Args) -> Result<()> {
-    let duration_str = match &args.preset {
-        Some(preset) => get_duration_from_preset(preset),
-        None => args.duration.clone().unwrap_or_default(),
+    let durations = match &args.preset {
+        Some(preset) => vec![get_duration_from_preset(&merged_presets(args)?, preset)],
+        None if args.stdin => vec![read_duration_line(&mut io::stdin().lock())?],
+        None => args
+            .duration
+            .iter()
+            .map(|duration| match duration.as_str() {
+                "-" => read_duration_line(&mut io::stdin().lock()),
+                _ => Ok(duration.clone()),
+            })
+            .collect::<Result<_>>()?,
     };
 
-    let duration =
-        parse_duration(&duration_str).map_err(|_| TempusError::InvalidDuration(duration_str))?;
+    // Parse everything up front so a typo in a later segment fails before the chain starts
+    let segments = timer_segments(&durations, &args.name)
+        .into_iter()
+        .map(|(name, duration)| Ok((name, parse_duration(&duration)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let start_at = args.start_at.as_deref().map(parse_datetime).transpose()?;
+    let loop_until = args.loop_until.as_deref().map(parse_datetime).transpose()?;
+    if let Some(deadline) = loop_until {
+        time_until(deadline)?;
+    }
+    // `--loop-until` repeats like `--repeat inf`, with the deadline ending it
+    let repeat = match loop_until {
+        Some(_) => Repeat::Forever,
+        None => args.repeat,
+    };
 
     let theme = parse_theme(&args.theme);
+    let options = timer_options(args, theme, args.bell, args.notify);
 
-    if args.big {
-        return progress::run_big_clock(duration, &args.name, args.bell)
-            .map_err(TempusError::IoError);
+    if args.dry_run {
+        if let Some(start_at) = start_at {
+            print_start_at(start_at, options.clock_format);
+        }
+        match loop_until {
+            Some(deadline) => {
+                print_timer_plan(&segments, Repeat::Times(1));
+                println!(
+                    "Repeated until {} {}",
+                    deadline.format("%Y-%m-%d"),
+                    options.clock_format.format(&deadline)
+                );
+            }
+            None => print_timer_plan(&segments, repeat),
+        }
+        return Ok(());
     }
 
-    if args.focus {
-        focus_mode::run_focus_mode(duration, &args.name, theme, args.bell, args.notify)?;
-    } else {
-        run_timer(
-            duration,
-            &args.name,
-            args.verbose,
-            theme,
-            args.bell,
-            args.notify,
-            args.use_12h,
-        )?;
+    if let Some(start_at) = start_at {
+        progress::wait_until(start_at, &options)?;
+    }
+
+    let start_time = Instant::now();
+    let mut round = 1;
+    let mut runs = 0;
+    'rounds: while repeat.runs(round) {
+        for (name, duration) in &segments {
+            let duration = match loop_until {
+                Some(deadline) => match loop_run_length(*duration, deadline, Local::now()) {
+                    Some(duration) => duration,
+                    None => break 'rounds,
+                },
+                None => *duration,
+            };
+            let name = repeat.label(name, round);
+            let outcome = if args.big {
+                progress::run_big_clock(duration, &name, &options)?
+            } else if args.focus {
+                focus_mode::run_focus_mode(duration, &name, &options)?
+            } else {
+                run_timer(duration, &name, &options)?
+            };
+            // Stopping one timer ends the whole chain, and any remaining rounds
+            if outcome == TimerOutcome::Stopped {
+                return Ok(());
+            }
+            runs += 1;
+        }
+        round += 1;
+    }
+
+    if options.format == OutputFormat::Text {
+        if let Some(deadline) = loop_until {
+            println!(
+                "Reached {} after {} run{} (took {})",
+                options.clock_format.format(&deadline),
+                runs,
+                if runs == 1 { "" } else { "s" },
+                format_precise_duration(start_time.elapsed())
+            );
+        } else if let Repeat::Times(rounds @ 2..) = repeat {
+            println!(
+                "All {} rounds completed! (took {})",
+                rounds,
+                format_precise_duration(start_time.elapsed())
+            );
+        }
     }
 
     Ok(())
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn main() -> ExitCode {
+    match run(&Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The timer has already said so on its own output
+        Err(TempusError::Interrupted) => ExitCode::from(EXIT_INTERRUPTED),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
 
+fn run(args: &Args) -> Result<()> {
     match &args.command {
-        Some(cmd) => handle_countdown(cmd),
+        Some(Command::ListPresets) => {
+            list_presets(&merged_presets(args)?);
+            Ok(())
+        }
+        Some(Command::ListThemes) => {
+            progress::list_themes(themes::custom_theme(&load_config()));
+            Ok(())
+        }
+        Some(Command::Interval {
+            work,
+            rest,
+            rounds,
+            name,
+        }) => {
+            let segments =
+                progress::interval_segments(parse_duration(work)?, parse_duration(rest)?, *rounds);
+            let options = timer_options(args, parse_theme(&args.theme), args.bell, args.notify);
+            progress::run_intervals(name, &segments, &options)?;
+            Ok(())
+        }
+        Some(Command::Pomodoro {
+            rounds,
+            work,
+            short_break,
+            long_break,
+        }) => {
+            let presets = merged_presets(args)?;
+            let length = |value: &Option<String>, preset: &str| match value {
+                Some(value) => parse_duration(value),
+                None => parse_duration(&get_duration_from_preset(&presets, preset)),
+            };
+            let segments = progress::pomodoro_segments(
+                length(work, "pomodoro")?,
+                length(short_break, "short-break")?,
+                length(long_break, "long-break")?,
+                *rounds,
+            );
+            let options = timer_options(args, parse_theme(&args.theme), args.bell, args.notify);
+            progress::run_pomodoro(&segments, &options)?;
+            Ok(())
+        }
+        Some(Command::Multi { timers }) => {
+            let options = timer_options(args, parse_theme(&args.theme), args.bell, args.notify);
+            progress::run_multi(timers, &options)?;
+            Ok(())
+        }
+        Some(Command::Stopwatch { name, theme }) => {
+            let options = timer_options(args, parse_theme(theme), args.bell, args.notify);
+            progress::run_stopwatch(name, &options)
+        }
+        Some(Command::Alarm { datetime, name }) => handle_alarm(args, datetime, name),
+        Some(Command::Resume { id, theme, big }) => handle_resume(args, id, theme, *big),
+        Some(Command::Status { id }) => handle_status(id),
+        Some(cmd) => handle_countdown(args, cmd),
         None => {
-            if args.duration.is_none() && args.preset.is_none() {
-                eprintln!(
-                    "Error: Either DURATION or --preset must be provided when not using a subcommand"
-                );
-                process::exit(1);
+            if let Some(time) = args.until_next {
+                return handle_until_next(args, time);
             }
+            if args.duration.is_empty() && args.preset.is_none() && !args.stdin {
+                return Err(TempusError::MissingDuration);
+            }
+
+            handle_timer(args)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Berlin;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32, sec: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, sec).unwrap()
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn segments_are_named_after_their_position() {
+        let pairs = |durations: &[&str], names: &[&str]| {
+            timer_segments(&strings(durations), &strings(names))
+        };
+        let pair = |name: &str, duration: &str| (name.to_string(), duration.to_string());
+
+        assert_eq!(pairs(&["5m"], &[]), [pair("Timer", "5m")]);
+        assert_eq!(
+            pairs(&["25m", "5m", "25m"], &[]),
+            [
+                pair("Timer 1", "25m"),
+                pair("Timer 2", "5m"),
+                pair("Timer 3", "25m")
+            ]
+        );
+        assert_eq!(
+            pairs(&["25m", "5m", "25m"], &["Work", "Break"]),
+            [
+                pair("Work", "25m"),
+                pair("Break", "5m"),
+                pair("Timer 3", "25m")
+            ]
+        );
+        assert_eq!(pairs(&["5m"], &["Tea", "Surplus"]), [pair("Tea", "5m")]);
+        assert_eq!(pairs(&[], &["Unused"]), []);
+    }
+
+    #[test]
+    fn each_dash_reads_its_own_line() {
+        let mut input = io::Cursor::new("25m\n  5m \r\n\n");
+        assert_eq!(read_duration_line(&mut input).unwrap(), "25m");
+        assert_eq!(read_duration_line(&mut input).unwrap(), "5m");
+        // A blank line, then the end of the input
+        for _ in 0..2 {
+            assert!(matches!(
+                read_duration_line(&mut input),
+                Err(TempusError::InvalidDuration(message)) if message == "no duration on stdin"
+            ));
+        }
+    }
+
+    #[test]
+    fn repeat_takes_a_count_or_forever() {
+        assert_eq!(parse_repeat("1"), Ok(Repeat::Times(1)));
+        assert_eq!(parse_repeat("5"), Ok(Repeat::Times(5)));
+        assert_eq!(parse_repeat("0"), Ok(Repeat::Forever));
+        assert_eq!(parse_repeat("inf"), Ok(Repeat::Forever));
+        assert_eq!(parse_repeat("INF"), Ok(Repeat::Forever));
+        for value in ["", "-1", "2.5", "many"] {
+            assert!(parse_repeat(value).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn repeated_rounds_are_numbered() {
+        assert_eq!(Repeat::Times(1).label("Tea", 1), "Tea");
+        assert_eq!(Repeat::Times(3).label("Tea", 2), "Tea (2/3)");
+        assert_eq!(Repeat::Forever.label("Tea", 12), "Tea (12)");
+
+        assert!(Repeat::Times(3).runs(3));
+        assert!(!Repeat::Times(3).runs(4));
+        assert!(Repeat::Forever.runs(u32::MAX));
+    }
+
+    #[test]
+    fn multi_timers_take_an_optional_name() {
+        let spec = |spec| parse_multi_spec(spec).unwrap();
+        assert_eq!(
+            spec("25m:Deep work"),
+            ("Deep work".to_string(), Duration::from_secs(1500))
+        );
+        assert_eq!(spec("90s"), ("90s".to_string(), Duration::from_secs(90)));
+        assert_eq!(
+            spec(" 1h :  "),
+            ("1h".to_string(), Duration::from_secs(3600))
+        );
+        assert_eq!(
+            spec("5m:Tea: green"),
+            ("Tea: green".to_string(), Duration::from_secs(300))
+        );
+        assert!(parse_multi_spec("soon:Tea").is_err());
+        assert!(parse_multi_spec(":Tea").is_err());
+    }
+
+    #[test]
+    fn loop_runs_are_cut_short_at_the_deadline() {
+        let deadline = local(2030, 1, 1, 12, 0, 0);
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let run = |now| loop_run_length(minutes(25), deadline, now);
+        assert_eq!(run(local(2030, 1, 1, 11, 0, 0)), Some(minutes(25)));
+        assert_eq!(run(local(2030, 1, 1, 11, 35, 0)), Some(minutes(25)));
+        assert_eq!(run(local(2030, 1, 1, 11, 50, 0)), Some(minutes(10)));
+        assert_eq!(run(local(2030, 1, 1, 12, 0, 0)), None);
+        assert_eq!(run(local(2030, 1, 1, 12, 0, 1)), None);
+    }
+
+    #[test]
+    fn user_presets_replace_builtins_and_invalid_ones_are_skipped() {
+        let mut presets = BTreeMap::from([
+            ("pomodoro".to_string(), "25m".to_string()),
+            ("tea".to_string(), "3m".to_string()),
+        ]);
+        let overrides = BTreeMap::from([
+            ("tea".to_string(), "4m".to_string()),
+            ("focus".to_string(), "50m".to_string()),
+            ("broken".to_string(), "soon".to_string()),
+        ]);
+        add_presets(&mut presets, overrides, "a test");
+        assert_eq!(
+            presets,
+            BTreeMap::from([
+                ("focus".to_string(), "50m".to_string()),
+                ("pomodoro".to_string(), "25m".to_string()),
+                ("tea".to_string(), "4m".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn preset_file_is_merged_over_the_builtins() {
+        let path =
+            std::env::temp_dir().join(format!("tempus-presets-test-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "pomodoro = \"30m\"\nstandup = \"15m\"\nbroken = \"soon\"\n",
+        )
+        .unwrap();
+        let args = Args::parse_from(["tempus", "--preset-file", path.to_str().unwrap()]);
+        let presets = merged_presets(&args);
+        std::fs::remove_file(&path).unwrap();
+
+        let presets = presets.unwrap();
+        assert_eq!(presets["pomodoro"], "30m");
+        assert_eq!(presets["standup"], "15m");
+        assert!(!presets.contains_key("broken"));
+        assert!(presets.contains_key("short-break"));
+    }
+
+    #[test]
+    fn missing_preset_file_is_an_error() {
+        let args = Args::parse_from(["tempus", "--preset-file", "/nonexistent/presets.toml"]);
+        assert!(matches!(
+            merged_presets(&args),
+            Err(TempusError::PresetFile(_))
+        ));
+    }
+
+    #[test]
+    fn head_start_is_the_time_since_from() {
+        let now = local(2030, 1, 1, 12, 0, 0);
+        assert_eq!(head_start(None, now), Duration::ZERO);
+        assert_eq!(
+            head_start(Some(local(2030, 1, 1, 9, 30, 0)), now),
+            Duration::from_secs(150 * 60)
+        );
+        assert_eq!(head_start(Some(now), now), Duration::ZERO);
+        assert_eq!(
+            head_start(Some(local(2030, 1, 1, 12, 0, 1)), now),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn until_next_moves_on_a_day_at_a_time() {
+        let time = NaiveTime::from_hms_opt(7, 0, 0).unwrap();
+        let previous = local(2030, 1, 1, 7, 0, 0);
+        assert_eq!(
+            next_occurrence(previous, local(2030, 1, 1, 7, 0, 1), time),
+            Some(local(2030, 1, 2, 7, 0, 0))
+        );
+        // Asleep through several days: the next one still ahead
+        assert_eq!(
+            next_occurrence(previous, local(2030, 1, 5, 9, 0, 0), time),
+            Some(local(2030, 1, 6, 7, 0, 0))
+        );
+        assert_eq!(
+            next_occurrence(previous, local(2030, 1, 5, 6, 0, 0), time),
+            Some(local(2030, 1, 5, 7, 0, 0))
+        );
+    }
+
+    #[test]
+    fn until_next_rolls_over_a_dst_gap() {
+        // Berlin skips from 02:00 to 03:00 on 2025-03-30
+        let time = NaiveTime::from_hms_opt(2, 30, 0).unwrap();
+        let berlin = |d, h, min| Berlin.with_ymd_and_hms(2025, 3, d, h, min, 0).unwrap();
+
+        let previous = berlin(29, 2, 30);
+        let in_gap = next_occurrence(previous, previous, time).unwrap();
+        assert_eq!(in_gap, berlin(30, 3, 0));
+        assert_eq!(
+            next_occurrence(in_gap, in_gap, time),
+            Some(berlin(31, 2, 30))
+        );
+    }
+
+    #[test]
+    fn status_shows_the_time_left_then_done() {
+        let countdown = state::PersistedCountdown {
+            name: "Launch".to_string(),
+            target: local(2030, 1, 1, 12, 0, 0),
+            from: None,
+        };
+        let status = |now| status_line("launch", Some(&countdown), now).unwrap();
+        assert_eq!(status(local(2030, 1, 1, 10, 30, 15)), "1h 29m 45s");
+        assert_eq!(status(local(2030, 1, 1, 11, 59, 59)), "1s");
+        assert_eq!(status(local(2030, 1, 1, 12, 0, 0)), "done");
+        assert_eq!(status(local(2030, 1, 2, 0, 0, 0)), "done");
+    }
+
+    #[test]
+    fn status_of_an_unknown_id_is_an_error() {
+        assert!(matches!(
+            status_line("nosuch", None, local(2030, 1, 1, 0, 0, 0)),
+            Err(TempusError::UnknownPersistId(id)) if id == "nosuch"
+        ));
+    }
+
+    #[test]
+    fn alarm_targets_resolve_like_countdown_targets() {
+        let args = Args::parse_from(["tempus", "alarm", "07:00"]);
+        let target = |datetime| countdown_target(&args, datetime);
+        let secs_ahead = |datetime| (target(datetime).unwrap() - Local::now()).num_seconds();
 
-            handle_timer(&args)
+        assert_eq!(
+            target("2999-01-01 07:00").unwrap(),
+            local(2999, 1, 1, 7, 0, 0)
+        );
+        assert!((0..=86_400).contains(&secs_ahead("07:00")));
+        for datetime in ["in 25m", "25m", "pomodoro"] {
+            assert!(
+                (1_495..=1_500).contains(&secs_ahead(datetime)),
+                "{datetime}"
+            );
         }
+        assert!(matches!(
+            target("someday"),
+            Err(TempusError::InvalidDateTime(_))
+        ));
+        assert!(matches!(
+            time_until(target("2020-01-01 00:00").unwrap()),
+            Err(TempusError::PastDateTime)
+        ));
     }
 }
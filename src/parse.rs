@@ -0,0 +1,639 @@
+use crate::{Result, TempusError};
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Weekday,
+};
+use std::time::Duration;
+
+/// Upper bound on how far a wall-clock time inside a DST gap is rolled forward.
+/// Real-world gaps are at most a couple of hours, so a day is plenty.
+const MAX_DST_GAP_MINUTES: i64 = 24 * 60;
+
+/// Resolve a wall-clock time in `tz` to a concrete instant.
+///
+/// Times inside a DST overlap ("fall back") resolve to the earliest of the two
+/// instants, and times inside a DST gap ("spring forward") are rolled forward to
+/// the first wall-clock minute that actually exists.
+fn resolve_local_datetime<Tz: TimeZone>(tz: &Tz, ndt: &NaiveDateTime) -> Option<DateTime<Tz>> {
+    match tz.from_local_datetime(ndt) {
+        LocalResult::Single(dt) => Some(dt),
+        // Not every backend orders the candidates, so pick the earlier one explicitly
+        LocalResult::Ambiguous(a, b) => Some(a.min(b)),
+        LocalResult::None => (1..=MAX_DST_GAP_MINUTES)
+            .filter_map(|mins| ndt.checked_add_signed(chrono::Duration::minutes(mins)))
+            .find_map(|candidate| tz.from_local_datetime(&candidate).earliest()),
+    }
+}
+
+/// Resolve a time of day to its next occurrence after `now`: today if it is
+/// still ahead, tomorrow otherwise.
+pub fn next_time_of_day<Tz: TimeZone>(now: DateTime<Tz>, nt: NaiveTime) -> Option<DateTime<Tz>> {
+    let tz = now.timezone();
+    let today = now.date_naive();
+    let dt = resolve_local_datetime(&tz, &today.and_time(nt))?;
+    if dt > now {
+        return Some(dt);
+    }
+
+    let tomorrow = today.succ_opt()?;
+    resolve_local_datetime(&tz, &tomorrow.and_time(nt))
+}
+
+/// Map the `noon` and `midnight` keywords to their time of day.
+fn parse_time_keyword(datetime: &str) -> Option<NaiveTime> {
+    match datetime.trim().to_lowercase().as_str() {
+        "noon" => NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => Some(NaiveTime::MIN),
+        _ => None,
+    }
+}
+
+/// Parse a bare time of day: `HH:MM`, `HH:MM:SS`, `noon` or `midnight`.
+pub fn parse_time_of_day(time: &str) -> Result<NaiveTime> {
+    parse_time_keyword(time)
+        .or_else(|| NaiveTime::parse_from_str(time, "%H:%M:%S").ok())
+        .or_else(|| NaiveTime::parse_from_str(time, "%H:%M").ok())
+        .ok_or_else(|| TempusError::InvalidDateTime(time.to_string()))
+}
+
+/// Parse a countdown target: a date and/or time (`2025-12-31 23:59`,
+/// `2025/12/31`, `20:00`, `noon`), RFC 3339, a time with a zone, a Unix
+/// timestamp (`@1767225600`), a weekday (`next monday 09:00`) or a relative
+/// time (`in 90 minutes`). A bare time of day is its next occurrence.
+pub fn parse_datetime(datetime: &str) -> Result<DateTime<Local>> {
+    if let Ok(nt) = parse_time_of_day(datetime) {
+        return next_time_of_day(Local::now(), nt)
+            .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()));
+    }
+
+    DateTime::parse_from_rfc3339(datetime)
+        .map(|dt| dt.with_timezone(&Local))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S")
+                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
+                .and_then(|ndt| {
+                    resolve_local_datetime(&Local, &ndt)
+                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+                })
+        })
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M")
+                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
+                .and_then(|ndt| {
+                    resolve_local_datetime(&Local, &ndt)
+                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+                })
+        })
+        .or_else(|_| {
+            NaiveDate::parse_from_str(datetime, "%Y-%m-%d")
+                .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
+                .and_then(|nd| {
+                    nd.and_hms_opt(0, 0, 0)
+                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+                })
+                .and_then(|ndt| {
+                    resolve_local_datetime(&Local, &ndt)
+                        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+                })
+        })
+        .or_else(|_| parse_local_datetime(datetime, "%Y/%m/%d %H:%M:%S"))
+        .or_else(|_| parse_local_datetime(datetime, "%Y/%m/%d %H:%M"))
+        .or_else(|_| parse_date_only(datetime, "%Y/%m/%d"))
+        .or_else(|_| parse_day_first_datetime(datetime))
+        .or_else(|_| parse_zoned_datetime(datetime))
+        .or_else(|_| parse_epoch_datetime(datetime))
+        .or_else(|_| parse_weekday_datetime(datetime))
+        .or_else(|_| parse_relative_datetime(datetime))
+        .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))
+}
+
+/// Parse a local date and time in `format`.
+fn parse_local_datetime(datetime: &str, format: &str) -> Result<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(datetime, format)
+        .ok()
+        .and_then(|ndt| resolve_local_datetime(&Local, &ndt))
+        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+}
+
+/// Parse a day-first date such as `31-12-2025`, optionally followed by a
+/// time. Only with a four-digit year, so `01-02-03` isn't read as the year 3.
+fn parse_day_first_datetime(datetime: &str) -> Result<DateTime<Local>> {
+    let date = datetime.split_whitespace().next().unwrap_or_default();
+    let year = date.rsplit('-').next().unwrap_or_default();
+    if year.len() != 4 {
+        return Err(TempusError::InvalidDateTime(datetime.to_string()));
+    }
+    parse_local_datetime(datetime, "%d-%m-%Y %H:%M:%S")
+        .or_else(|_| parse_local_datetime(datetime, "%d-%m-%Y %H:%M"))
+        .or_else(|_| parse_date_only(datetime, "%d-%m-%Y"))
+}
+
+/// Parse a date without a time in `format`, counting down to its midnight.
+fn parse_date_only(datetime: &str, format: &str) -> Result<DateTime<Local>> {
+    NaiveDate::parse_from_str(datetime, format)
+        .ok()
+        .and_then(|nd| nd.and_hms_opt(0, 0, 0))
+        .and_then(|ndt| resolve_local_datetime(&Local, &ndt))
+        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+}
+
+/// Parse a UTC offset suffix: `UTC`/`GMT`/`Z`, `+HHMM`, `+HH:MM` or `+HH`.
+fn parse_utc_offset(zone: &str) -> Option<FixedOffset> {
+    if ["utc", "gmt", "z"].contains(&zone.to_lowercase().as_str()) {
+        return FixedOffset::east_opt(0);
+    }
+
+    let sign = match zone.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = zone[1..].replace(':', "");
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, mins) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if mins >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + mins * 60))
+}
+
+/// Parse a date/time followed by an explicit zone, e.g. "2025-12-31 23:59:59 UTC"
+/// or "2025-12-31 23:59 +0900", converted to local time.
+fn parse_zoned_datetime(datetime: &str) -> Result<DateTime<Local>> {
+    let invalid = || TempusError::InvalidDateTime(datetime.to_string());
+
+    let (naive, zone) = datetime.trim().rsplit_once(' ').ok_or_else(invalid)?;
+    let offset = parse_utc_offset(zone).ok_or_else(invalid)?;
+    let ndt = NaiveDateTime::parse_from_str(naive.trim(), "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(naive.trim(), "%Y-%m-%d %H:%M"))
+        .map_err(|_| invalid())?;
+
+    offset
+        .from_local_datetime(&ndt)
+        .single()
+        .map(|dt| dt.with_timezone(&Local))
+        .ok_or_else(invalid)
+}
+
+/// Parse a Unix epoch target such as "@1735689599" or "@1735689599.5".
+fn parse_epoch_datetime(datetime: &str) -> Result<DateTime<Local>> {
+    let invalid = || TempusError::InvalidDateTime(datetime.to_string());
+
+    let epoch = datetime.trim().strip_prefix('@').ok_or_else(invalid)?;
+    let (whole, fraction) = epoch.split_once('.').unwrap_or((epoch, ""));
+    if fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let mut secs = whole.parse::<i64>().map_err(|_| invalid())?;
+    let mut nanos = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<9}", fraction)
+            .parse::<u32>()
+            .map_err(|_| invalid())?
+    };
+    // The fraction extends the timestamp away from zero, so negative epochs
+    // borrow a second to keep the nanoseconds positive
+    if whole.starts_with('-') && nanos > 0 {
+        secs = secs.checked_sub(1).ok_or_else(invalid)?;
+        nanos = 1_000_000_000 - nanos;
+    }
+
+    Local
+        .timestamp_opt(secs, nanos)
+        .single()
+        .ok_or_else(invalid)
+}
+
+/// Parse a weekday target such as "friday", "next mon 09:00" or "Sunday 17:30:00".
+///
+/// Resolves to the next occurrence of that weekday (today included) at the given
+/// time, defaulting to midnight. Targets that have already passed move a week ahead.
+fn parse_weekday_datetime(datetime: &str) -> Result<DateTime<Local>> {
+    weekday_target(datetime, Local::now())
+}
+
+/// The weekday target `datetime` names, as seen from `now`.
+fn weekday_target(datetime: &str, now: DateTime<Local>) -> Result<DateTime<Local>> {
+    let invalid = || TempusError::InvalidDateTime(datetime.to_string());
+
+    // "next" only reads nicer; "next friday" and "friday" resolve the same
+    let mut words = datetime.split_whitespace().peekable();
+    words.next_if(|word| word.eq_ignore_ascii_case("next"));
+
+    let weekday = words
+        .next()
+        .and_then(|word| word.parse::<Weekday>().ok())
+        .ok_or_else(invalid)?;
+    let time = match words.next() {
+        Some(time) => NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .map_err(|_| invalid())?,
+        None => NaiveTime::MIN,
+    };
+    if words.next().is_some() {
+        return Err(invalid());
+    }
+
+    let today = now.date_naive();
+    let days_ahead =
+        (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    let date = today
+        .checked_add_days(Days::new(days_ahead.into()))
+        .ok_or_else(invalid)?;
+    let target = resolve_local_datetime(&Local, &date.and_time(time)).ok_or_else(invalid)?;
+    if target > now {
+        return Ok(target);
+    }
+
+    let next_week = date.checked_add_days(Days::new(7)).ok_or_else(invalid)?;
+    resolve_local_datetime(&Local, &next_week.and_time(time)).ok_or_else(invalid)
+}
+
+/// Parse a relative target such as "in 5 minutes" or "In 2h30m" into an
+/// absolute date/time measured from now.
+fn parse_relative_datetime(datetime: &str) -> Result<DateTime<Local>> {
+    let trimmed = datetime.trim();
+    let expr = trimmed
+        .get(..3)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("in "))
+        .map(|_| trimmed[3..].trim())
+        .filter(|expr| !expr.is_empty())
+        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))?;
+
+    let duration =
+        parse_duration(expr).map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))?;
+    let duration = chrono::Duration::from_std(duration)
+        .map_err(|_| TempusError::InvalidDateTime(datetime.to_string()))?;
+
+    Local::now()
+        .checked_add_signed(duration)
+        .ok_or_else(|| TempusError::InvalidDateTime(datetime.to_string()))
+}
+
+/// Parse a duration, additionally accepting a single decimal amount such as
+/// `1.5h`, `0.5m` or `2.25s` that humantime rejects. Anything else, including
+/// compound forms like `1h30m` or `1h 30m`, is handed to humantime. Commas
+/// between the parts, as in `1h, 30m`, are read as spaces; separate
+/// arguments stay separate timers in a chain.
+pub fn parse_duration(duration_str: &str) -> Result<Duration> {
+    let invalid = || TempusError::InvalidDuration(duration_str.to_string());
+
+    let normalized = duration_str.replace(',', " ");
+    let trimmed = normalized.trim();
+    let decimal = trimmed
+        .char_indices()
+        .last()
+        .and_then(|(idx, unit)| {
+            let secs_per_unit = match unit {
+                's' => 1.0,
+                'm' => 60.0,
+                'h' => 3600.0,
+                'd' => 86400.0,
+                _ => return None,
+            };
+            Some((&trimmed[..idx], secs_per_unit))
+        })
+        .filter(|(amount, _)| {
+            amount.split_once('.').is_some_and(|(whole, frac)| {
+                !whole.is_empty()
+                    && !frac.is_empty()
+                    && whole
+                        .chars()
+                        .chain(frac.chars())
+                        .all(|c| c.is_ascii_digit())
+            })
+        });
+
+    match decimal {
+        Some((amount, secs_per_unit)) => {
+            let amount = amount.parse::<f64>().map_err(|_| invalid())?;
+            Duration::try_from_secs_f64(amount * secs_per_unit).map_err(|_| invalid())
+        }
+        None => humantime::parse_duration(trimmed).map_err(|_| invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// How far ahead of now `target` is, to the nearest second.
+    fn secs_ahead(target: DateTime<Local>) -> i64 {
+        ((target - Local::now()).num_milliseconds() as f64 / 1000.0).round() as i64
+    }
+
+    #[test]
+    fn relative_target_is_measured_from_now() {
+        assert_eq!(secs_ahead(parse_relative_datetime("in 5s").unwrap()), 5);
+        assert_eq!(
+            secs_ahead(parse_relative_datetime("in 1h30m").unwrap()),
+            90 * 60
+        );
+        assert_eq!(
+            secs_ahead(parse_relative_datetime("  in 90 minutes ").unwrap()),
+            90 * 60
+        );
+    }
+
+    #[test]
+    fn relative_prefix_is_case_insensitive() {
+        assert_eq!(secs_ahead(parse_relative_datetime("IN 5m").unwrap()), 300);
+        assert_eq!(secs_ahead(parse_relative_datetime("In 5m").unwrap()), 300);
+    }
+
+    #[test]
+    fn relative_target_needs_a_duration() {
+        assert!(parse_relative_datetime("in ").is_err());
+        assert!(parse_relative_datetime("in").is_err());
+        assert!(parse_relative_datetime("in soon").is_err());
+        assert!(parse_relative_datetime("5m").is_err());
+    }
+
+    #[test]
+    fn time_in_a_dst_gap_lands_after_the_gap() {
+        // Clocks in Berlin jumped from 02:00 to 03:00 on 2025-03-30
+        let tz = chrono_tz::Europe::Berlin;
+        let gap = NaiveDate::from_ymd_opt(2025, 3, 30)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let resolved = resolve_local_datetime(&tz, &gap).unwrap();
+        assert_eq!(
+            resolved.naive_local(),
+            NaiveDate::from_ymd_opt(2025, 3, 30)
+                .unwrap()
+                .and_hms_opt(3, 0, 0)
+                .unwrap()
+        );
+        assert_eq!(resolved.offset().to_string(), "CEST");
+    }
+
+    #[test]
+    fn ambiguous_time_picks_the_earliest_instant() {
+        // Clocks in Berlin went back from 03:00 to 02:00 on 2025-10-26
+        let tz = chrono_tz::Europe::Berlin;
+        let overlap = NaiveDate::from_ymd_opt(2025, 10, 26)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let resolved = resolve_local_datetime(&tz, &overlap).unwrap();
+        assert_eq!(resolved.naive_local(), overlap);
+        assert_eq!(resolved.offset().to_string(), "CEST");
+        assert_eq!(
+            resolved,
+            tz.from_local_datetime(&overlap).earliest().unwrap()
+        );
+    }
+
+    #[test]
+    fn fixed_offset_times_resolve_as_they_are() {
+        let tz = FixedOffset::east_opt(9 * 3600).unwrap();
+        let ndt = NaiveDate::from_ymd_opt(2025, 3, 30)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        assert_eq!(
+            resolve_local_datetime(&tz, &ndt).unwrap().naive_local(),
+            ndt
+        );
+    }
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn same_weekday_means_next_week_once_its_time_has_passed() {
+        // 2025-10-15 was a Wednesday
+        let now = local(2025, 10, 15, 10, 0);
+        assert_eq!(
+            weekday_target("wednesday", now).unwrap(),
+            local(2025, 10, 22, 0, 0)
+        );
+        assert_eq!(
+            weekday_target("next wednesday", now).unwrap(),
+            local(2025, 10, 22, 0, 0)
+        );
+        assert_eq!(
+            weekday_target("wednesday 12:00", now).unwrap(),
+            local(2025, 10, 15, 12, 0)
+        );
+    }
+
+    #[test]
+    fn weekday_targets_the_coming_day() {
+        let now = local(2025, 10, 15, 10, 0);
+        assert_eq!(
+            weekday_target("thursday", now).unwrap(),
+            local(2025, 10, 16, 0, 0)
+        );
+        assert_eq!(
+            weekday_target("friday 14:00", now).unwrap(),
+            local(2025, 10, 17, 14, 0)
+        );
+        assert_eq!(
+            weekday_target("Tuesday 09:30:00", now).unwrap(),
+            local(2025, 10, 21, 9, 30)
+        );
+    }
+
+    #[test]
+    fn weekday_names_can_be_abbreviated() {
+        let now = local(2025, 10, 15, 10, 0);
+        assert_eq!(
+            weekday_target("thu", now).unwrap(),
+            local(2025, 10, 16, 0, 0)
+        );
+        assert_eq!(
+            weekday_target("next Fri 14:00", now).unwrap(),
+            local(2025, 10, 17, 14, 0)
+        );
+    }
+
+    #[test]
+    fn weekday_rejects_extra_words_and_bad_times() {
+        let now = local(2025, 10, 15, 10, 0);
+        assert!(weekday_target("friday 25:00", now).is_err());
+        assert!(weekday_target("friday 14:00 sharp", now).is_err());
+        assert!(weekday_target("next", now).is_err());
+        assert!(weekday_target("someday", now).is_err());
+    }
+
+    #[test]
+    fn epoch_target_is_that_instant() {
+        let target = parse_epoch_datetime("@1700000000").unwrap();
+        assert_eq!(target.timestamp(), 1_700_000_000);
+        assert_eq!(target.timestamp_subsec_nanos(), 0);
+
+        let target = parse_epoch_datetime("@1700000000.25").unwrap();
+        assert_eq!(target.timestamp(), 1_700_000_000);
+        assert_eq!(target.timestamp_subsec_millis(), 250);
+    }
+
+    #[test]
+    fn out_of_range_epoch_is_an_error() {
+        assert!(parse_epoch_datetime("@99999999999999999").is_err());
+        assert!(parse_epoch_datetime("@999999999999999999999").is_err());
+        assert!(parse_epoch_datetime("@-9223372036854775808.5").is_err());
+    }
+
+    #[test]
+    fn malformed_epoch_is_an_error() {
+        assert!(parse_epoch_datetime("1700000000").is_err());
+        assert!(parse_epoch_datetime("@").is_err());
+        assert!(parse_epoch_datetime("@17e8").is_err());
+        assert!(parse_epoch_datetime("@1700000000.1234567891").is_err());
+    }
+
+    #[test]
+    fn decimal_durations_use_their_unit() {
+        assert_eq!(parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("0.25m").unwrap(), Duration::from_secs(15));
+        assert_eq!(
+            parse_duration("2.25s").unwrap(),
+            Duration::from_millis(2250)
+        );
+        assert_eq!(parse_duration("0.5d").unwrap(), Duration::from_secs(43200));
+    }
+
+    #[test]
+    fn decimal_durations_take_a_single_unit_only() {
+        assert!(parse_duration("1.5h30m").is_err());
+        assert!(parse_duration(".h").is_err());
+        assert!(parse_duration(".5h").is_err());
+        assert!(parse_duration("1.h").is_err());
+        assert!(parse_duration("1.5x").is_err());
+    }
+
+    #[test]
+    fn commas_separate_duration_parts() {
+        let ninety_minutes = Duration::from_secs(90 * 60);
+        assert_eq!(parse_duration("1h, 30m").unwrap(), ninety_minutes);
+        assert_eq!(parse_duration("1h,30m").unwrap(), ninety_minutes);
+        assert_eq!(parse_duration("1h 30m,").unwrap(), ninety_minutes);
+        assert_eq!(parse_duration("1h,,30m").unwrap(), ninety_minutes);
+        assert!(parse_duration(",").is_err());
+    }
+
+    #[test]
+    fn slashed_dates_take_a_time_with_or_without_seconds() {
+        assert_eq!(
+            parse_datetime("2999/12/25 09:00").unwrap(),
+            local(2999, 12, 25, 9, 0)
+        );
+        assert_eq!(
+            parse_datetime("2999/12/25 09:00:00").unwrap(),
+            local(2999, 12, 25, 9, 0)
+        );
+        assert_eq!(
+            parse_datetime("2999/12/25").unwrap(),
+            local(2999, 12, 25, 0, 0)
+        );
+    }
+
+    #[test]
+    fn day_first_dates_take_a_time_with_or_without_seconds() {
+        assert_eq!(
+            parse_datetime("25-12-2999 09:00").unwrap(),
+            local(2999, 12, 25, 9, 0)
+        );
+        assert_eq!(
+            parse_datetime("25-12-2999 09:00:00").unwrap(),
+            local(2999, 12, 25, 9, 0)
+        );
+        assert_eq!(
+            parse_datetime("25-12-2999").unwrap(),
+            local(2999, 12, 25, 0, 0)
+        );
+    }
+
+    #[test]
+    fn day_first_dates_need_a_four_digit_year() {
+        assert!(parse_datetime("25-12-99").is_err());
+        assert!(parse_datetime("25-12-99 09:00").is_err());
+        assert!(parse_datetime("12/25/2999").is_err());
+    }
+
+    #[test]
+    fn noon_and_midnight_are_times_of_day() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert_eq!(parse_time_of_day("noon").unwrap(), time(12, 0));
+        assert_eq!(parse_time_of_day(" Midnight ").unwrap(), time(0, 0));
+        assert_eq!(parse_time_of_day("07:05").unwrap(), time(7, 5));
+        assert_eq!(
+            parse_time_of_day("07:05:30").unwrap(),
+            NaiveTime::from_hms_opt(7, 5, 30).unwrap()
+        );
+        for time in ["25:00", "noonish", "7", ""] {
+            assert!(parse_time_of_day(time).is_err(), "{:?}", time);
+        }
+    }
+
+    #[test]
+    fn time_of_day_is_today_while_still_ahead() {
+        let now = local(2025, 10, 15, 10, 0);
+        let next = |time| next_time_of_day(now, parse_time_of_day(time).unwrap()).unwrap();
+        assert_eq!(next("noon"), local(2025, 10, 15, 12, 0));
+        assert_eq!(next("10:01"), local(2025, 10, 15, 10, 1));
+    }
+
+    #[test]
+    fn time_of_day_rolls_over_to_tomorrow_once_passed() {
+        let now = local(2025, 10, 15, 12, 0);
+        let next = |time| next_time_of_day(now, parse_time_of_day(time).unwrap()).unwrap();
+        // Exactly now has already passed
+        assert_eq!(next("noon"), local(2025, 10, 16, 12, 0));
+        assert_eq!(next("09:30"), local(2025, 10, 16, 9, 30));
+        assert_eq!(next("midnight"), local(2025, 10, 16, 0, 0));
+
+        let late = local(2025, 12, 31, 23, 59);
+        assert_eq!(
+            next_time_of_day(late, NaiveTime::MIN).unwrap(),
+            local(2026, 1, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn zoned_targets_take_an_offset_or_utc() {
+        let utc = |h, m, s| chrono::Utc.with_ymd_and_hms(2025, 12, 31, h, m, s).unwrap();
+        let zoned = |datetime| parse_zoned_datetime(datetime).unwrap();
+        assert_eq!(zoned("2025-12-31 23:59:59 UTC"), utc(23, 59, 59));
+        assert_eq!(zoned("2025-12-31 23:59 gmt"), utc(23, 59, 0));
+        assert_eq!(zoned("2025-12-31 23:59 Z"), utc(23, 59, 0));
+        assert_eq!(zoned("2025-12-31 23:59 +0900"), utc(14, 59, 0));
+        assert_eq!(zoned("2025-12-31 23:59:30 +09:00"), utc(14, 59, 30));
+        assert_eq!(zoned("2025-12-31 08:00 -05"), utc(13, 0, 0));
+        assert_eq!(zoned("2025-12-31 08:00 +05:30"), utc(2, 30, 0));
+    }
+
+    #[test]
+    fn malformed_zones_are_rejected() {
+        for datetime in [
+            "2025-12-31 23:59 PST",
+            "2025-12-31 23:59 +9",
+            "2025-12-31 23:59 +09:60",
+            "2025-12-31 23:59 +ab:cd",
+            "2025-12-31 23:59 +123",
+            "2025-12-31 UTC",
+            "23:59 UTC",
+            "2025-12-31 23:59",
+        ] {
+            assert!(parse_zoned_datetime(datetime).is_err(), "{:?}", datetime);
+        }
+    }
+}
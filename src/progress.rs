@@ -1,492 +1,1914 @@
-use crate::Result;
-use crate::focus_mode::render_big_time;
-use crate::utils::{format_simple_duration, send_notification, should_use_color};
+use crate::control::{self, ControlCommand, ControlSocket};
+use crate::focus_mode::{
+    ANIMATION_FRAME, BigFont, SCREEN_MARGIN, ScreenLayout, fit_big_font, progress_color,
+    pulse_color, rainbow_color, render_big_time, render_minimal, screen_layout,
+};
+use crate::history::{HistoryEntry, log_completion};
+use crate::themes::CustomTheme;
+use crate::utils::{
+    BellOptions, DEFAULT_COMPLETE_MSG, NotificationOptions, completion_alert, completion_alert_to,
+    completion_message, expand_name, flash_screen, format_simple_duration, is_interactive,
+    rgb_to_ansi256, send_milestone_notification, send_notification, should_color, should_use_color,
+    sleep_unless_key_pressed, speak, spoken_duration, supports_truecolor, write_atomic,
+};
+use crate::webhook::webhook_completion;
+use crate::{Result, TempusError};
 use chrono::{DateTime, Local};
+use clap::ValueEnum;
 use colorgrad;
 use colorgrad::Gradient;
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        self, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+    },
 };
 use ratatui::{
     Terminal,
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Margin},
     style::{Color, Modifier, Style},
-    text::Span,
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
 use std::f64::consts::PI;
-use std::io::Write;
-use std::io::stdout;
+use std::fs;
+use std::io::{self, IsTerminal, Write, stdout};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::sleep;
 use std::time::{Duration, Instant, SystemTime};
-use yansi::{Color as YansiColor, Paint};
+use yansi::{Color as YansiColor, Paint, Painted};
 
-#[derive(Debug, Clone, Copy)]
+mod json;
+mod multi;
+mod segments;
+mod stopwatch;
+
+use json::{TimerStatus, run_json_timer};
+pub use multi::run_multi;
+pub use segments::{interval_segments, pomodoro_segments, run_intervals, run_pomodoro};
+pub use stopwatch::run_stopwatch;
+
+#[derive(Debug, Clone)]
 pub enum ProgressBarTheme {
     Gradient,
     Rainbow,
     Plain,
     Pulse,
     Color,
+    /// Gradient and spinner colors from the `[theme.custom]` config table.
+    Custom(CustomTheme),
 }
 
 const PROGRESS_CHARS: [char; 9] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█', ' '];
+/// Braille cells with 0 to 8 dots raised, filling the left column bottom-up
+/// and then the right one, so the partial cell grows left to right.
+const BRAILLE_CHARS: [char; 9] = ['⠀', '⡀', '⡄', '⡆', '⡇', '⣇', '⣧', '⣷', '⣿'];
 const SPINNER_CHARS: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const ASCII_SPINNER_CHARS: [char; 4] = ['/', '-', '\\', '|'];
+
+const LEFT_BRACKET: char = '┃';
+const RIGHT_BRACKET: char = '┃';
+
+/// Glyphs used to draw the progress bar, overridable with `--chars`.
+#[derive(Debug, Clone, Copy)]
+pub struct BarChars {
+    pub fill: char,
+    pub empty: char,
+    pub left: char,
+    pub right: char,
+    /// Whether the leading cell shows how far into it the bar has filled,
+    /// using the ramp for `style`. Only meaningful with the glyphs that go
+    /// with that style.
+    pub partial: bool,
+    pub style: BarStyle,
+}
+
+impl Default for BarChars {
+    fn default() -> Self {
+        Self {
+            fill: PROGRESS_CHARS[7],
+            empty: PROGRESS_CHARS[8],
+            left: LEFT_BRACKET,
+            right: RIGHT_BRACKET,
+            partial: true,
+            style: BarStyle::Block,
+        }
+    }
+}
+
+impl BarChars {
+    /// Plain ASCII glyphs for terminals without Unicode block support.
+    pub fn ascii() -> Self {
+        Self {
+            fill: '#',
+            empty: '-',
+            left: '|',
+            right: '|',
+            partial: false,
+            style: BarStyle::Block,
+        }
+    }
+
+    /// Full Braille cells, with the partial cell filled one dot at a time.
+    pub fn braille() -> Self {
+        Self {
+            fill: BRAILLE_CHARS[8],
+            style: BarStyle::Braille,
+            ..Self::default()
+        }
+    }
+}
+
+/// Glyph ramp for the partially filled cell at the edge of the bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BarStyle {
+    /// Eighth blocks, `▏` to `█`.
+    #[default]
+    Block,
+    /// Braille dots, `⡀` to `⣿`.
+    Braille,
+}
+
+impl BarStyle {
+    /// The glyph for a cell that is `fraction` filled.
+    fn partial_glyph(self, fraction: f64) -> char {
+        match self {
+            BarStyle::Block => {
+                PROGRESS_CHARS[(fraction * (PROGRESS_CHARS.len() - 1) as f64).floor() as usize]
+            }
+            BarStyle::Braille => braille_glyph(fraction),
+        }
+    }
+}
+
+/// The Braille cell for `fraction` (0.0 to 1.0) of a cell: one more dot for
+/// every eighth, from blank to all eight dots.
+fn braille_glyph(fraction: f64) -> char {
+    let steps = (BRAILLE_CHARS.len() - 1) as f64;
+    BRAILLE_CHARS[(fraction.clamp(0.0, 1.0) * steps).floor() as usize]
+}
+
+/// Display and completion options for `run_timer`.
+#[derive(Debug, Clone)]
+pub struct TimerOptions {
+    pub verbose: bool,
+    pub theme: ProgressBarTheme,
+    pub bell: BellOptions,
+    pub notify: bool,
+    pub clock_format: ClockFormat,
+    pub chars: BarChars,
+    /// Use the ASCII spinner instead of the Braille one.
+    pub ascii: bool,
+    /// Command, urgency and timeout of the desktop notifications.
+    pub notification: NotificationOptions,
+    /// Percentages at which to send a progress notification.
+    pub notify_at: Vec<u8>,
+    /// Sound file played on completion in place of the bell.
+    pub sound: Option<PathBuf>,
+    pub format: OutputFormat,
+    /// File kept up to date with the remaining time, for external tools.
+    pub status_file: Option<PathBuf>,
+    /// Show the remaining time in the terminal window title.
+    pub set_title: bool,
+    /// Report progress to the terminal with OSC 9;4.
+    pub os_progress: bool,
+    /// CSV history file appended to when a timer completes.
+    pub log: Option<PathBuf>,
+    /// Skip the progress display and only print the completion line.
+    pub quiet: bool,
+    /// Remaining time at which the header turns red and the bell rings once.
+    pub warn_at: Option<Duration>,
+    /// Digit font for the big clock.
+    pub font: BigFont,
+    /// Ring the bell every this often during a focus session.
+    pub tick: Option<Duration>,
+    /// Amount the focus mode's `+`/`-` and arrow keys start out adjusting by.
+    pub step: Duration,
+    /// Start focus mode with its low-time warning turned on.
+    pub focus_notify: bool,
+    /// Remaining time at which focus mode's low-time warning starts.
+    pub focus_notify_at: Duration,
+    /// Template for the completion line, see `completion_message`.
+    pub complete_msg: Option<String>,
+    /// URL template posted to when a timer completes.
+    pub webhook: Option<String>,
+    /// Read the completion message aloud.
+    pub say: bool,
+    /// What the readout after the bar shows.
+    pub progress_style: ProgressStyle,
+    /// Whether the bar fills up or empties as time passes.
+    pub bar_direction: BarDirection,
+    /// Draw the spinner in front of the bar.
+    pub spinner: bool,
+    /// Time counted as already elapsed when the timer starts, for a countdown
+    /// with `--from`.
+    pub head_start: Duration,
+    /// Unix socket `run_timer` takes commands on while the bar is shown.
+    pub control_socket: Option<PathBuf>,
+    /// Call out the remaining time whenever it is a multiple of this.
+    pub announce_every: Option<Duration>,
+    /// Call out each of the last this many seconds.
+    pub count_in: u64,
+    /// Draw `run_timer` as one line fitted to the terminal width.
+    pub compact: bool,
+    /// Draw `run_timer`'s progress on stderr, leaving stdout to the
+    /// completion line.
+    pub progress_to_stderr: bool,
+}
+
+/// Best-effort writer for `--status-file`. A failed write never stops the
+/// timer; the first failure is reported once on stderr.
+struct StatusFile<'a> {
+    path: Option<&'a Path>,
+    last: String,
+    warned: bool,
+}
+
+impl<'a> StatusFile<'a> {
+    fn new(path: Option<&'a Path>) -> Self {
+        Self {
+            path,
+            last: String::new(),
+            warned: false,
+        }
+    }
+
+    fn update(&mut self, remaining: Duration, percent: f64) {
+        let Some(path) = self.path else {
+            return;
+        };
+        let contents = format!("{} {:.0}%\n", format_simple_duration(remaining), percent);
+        // The display redraws far more often than this text changes
+        if contents == self.last {
+            return;
+        }
+        match write_atomic(path, &contents) {
+            Ok(()) => self.last = contents,
+            Err(e) if !self.warned => {
+                self.warned = true;
+                eprintln!("Warning: could not write {}: {}", path.display(), e);
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Remove the file once the timer is over so nothing stale is left behind.
+    fn remove(&mut self) {
+        if let Some(path) = self.path {
+            let _ = fs::remove_file(path);
+            self.last = String::new();
+        }
+    }
+}
+
+/// What `run_timer` writes to stdout while it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// The interactive progress bar (plain lines when not a terminal)
+    #[default]
+    Text,
+    /// One JSON status object per second, for status bars
+    Json,
+}
+
+/// Progress milestones that have not been announced yet.
+struct Milestones {
+    /// Pending percentages, sorted ascending without duplicates.
+    pending: Vec<u8>,
+}
+
+impl Milestones {
+    fn new(percentages: &[u8]) -> Self {
+        let mut pending = percentages.to_vec();
+        pending.sort_unstable();
+        pending.dedup();
+        Self { pending }
+    }
+
+    /// Returns the lowest milestone reached by `percent` that hasn't fired
+    /// yet, retiring it so it fires only once. Call it until it returns `None`
+    /// to catch every milestone a slow frame jumped past.
+    fn crossed(&mut self, percent: f64) -> Option<u8> {
+        let &lowest = self.pending.first()?;
+        (f64::from(lowest) <= percent).then(|| self.pending.remove(0))
+    }
+}
+
+/// `--warn-at`: tracks whether the remaining time has dropped to the threshold
+/// and whether the warning bell has already rung for it.
+struct LowTimeWarning {
+    threshold: Option<Duration>,
+    rung: bool,
+}
+
+impl LowTimeWarning {
+    fn new(threshold: Option<Duration>) -> Self {
+        Self {
+            threshold,
+            rung: false,
+        }
+    }
+
+    fn active(&self, remaining: Duration) -> bool {
+        self.threshold
+            .is_some_and(|threshold| remaining <= threshold)
+    }
+
+    /// Returns true the first time `remaining` reaches the threshold, and never
+    /// again after that.
+    fn trigger(&mut self, remaining: Duration) -> bool {
+        if self.rung || !self.active(remaining) {
+            return false;
+        }
+        self.rung = true;
+        true
+    }
+}
+
+/// `--announce-every` and `--count-in`: decides when the remaining time is
+/// called out, exactly once per whole second however often the display
+/// redraws.
+struct Announcer {
+    /// `--announce-every` in whole seconds.
+    every: Option<u64>,
+    count_in: u64,
+    /// Whole seconds left at the previous check, rounded up.
+    last: Option<u64>,
+}
+
+impl Announcer {
+    fn new(every: Option<Duration>, count_in: u64) -> Self {
+        Self {
+            every: every.map(|every| every.as_secs().max(1)),
+            count_in,
+            last: None,
+        }
+    }
+
+    fn wanted(&self, secs: u64) -> bool {
+        secs > 0
+            && (secs <= self.count_in || self.every.is_some_and(|every| secs.is_multiple_of(every)))
+    }
+
+    /// Returns the whole seconds left to call out if `remaining` has reached
+    /// one since the last check. The first check only records the starting
+    /// point, and if a slow frame skipped several seconds only the lowest one
+    /// that is wanted is returned.
+    fn crossed(&mut self, remaining: Duration) -> Option<u64> {
+        // A second is reached when the time left drops to it, so 2.9s left is
+        // "3 seconds to go"
+        let secs = remaining.as_secs_f64().ceil() as u64;
+        let previous = self.last.replace(secs)?;
+        (secs..previous).find(|&secs| self.wanted(secs))
+    }
+}
+
+/// Call out `secs` seconds left: read it aloud with `--say`, otherwise ring
+/// the bell. The count-in is spoken as bare numbers.
+fn announce_remaining(out: &mut impl Write, options: &TimerOptions, secs: u64) {
+    if options.say {
+        let text = if secs <= options.count_in {
+            secs.to_string()
+        } else {
+            format!("{} left", spoken_duration(Duration::from_secs(secs)))
+        };
+        if let Err(e) = speak(&text) {
+            eprintln!("Warning: {}", e);
+        }
+    } else if options.bell.enabled {
+        let _ = write!(out, "\x07");
+    }
+}
+
+/// Read the completion message aloud if `--say` is set. `name` is the plain,
+/// unpainted timer name.
+pub fn say_completion(options: &TimerOptions, name: &str, duration: Duration, elapsed: Duration) {
+    if !options.say {
+        return;
+    }
+    let template = options
+        .complete_msg
+        .as_deref()
+        .unwrap_or(DEFAULT_COMPLETE_MSG);
+    if let Err(e) = speak(&completion_message(template, name, duration, elapsed)) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+fn notify_milestone(name: &str, percent: u8, remaining: Duration, options: &NotificationOptions) {
+    if let Err(e) = send_milestone_notification(name, percent, remaining, options) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+const MAX_BAR_WIDTH: usize = 40;
+const MIN_BAR_WIDTH: usize = 10;
+/// Columns taken by everything on the bar line except the bar itself: the
+/// spinner and its space, both brackets and the " 100.0%" readout.
+const BAR_LINE_CHROME: usize = 2 + 2 + 7;
 
-const LEFT_BRACKET: &str = "┃";
-const RIGHT_BRACKET: &str = "┃";
+/// What the readout after the bar shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ProgressStyle {
+    /// `48.0%`
+    #[default]
+    Percent,
+    /// `12m 0s/25m 0s`
+    Fraction,
+    /// `48.0% (12m 0s/25m 0s)`
+    Both,
+}
 
-pub fn run_timer(
+/// The readout after the bar for `style`: the percentage, elapsed/total time
+/// or both.
+fn progress_text(
+    style: ProgressStyle,
+    percent: f64,
+    elapsed: Duration,
     duration: Duration,
-    name: &str,
+) -> String {
+    let fraction = || {
+        format!(
+            "{}/{}",
+            format_simple_duration(elapsed.min(duration)),
+            format_simple_duration(duration)
+        )
+    };
+    match style {
+        ProgressStyle::Percent => format!("{:.1}%", percent),
+        ProgressStyle::Fraction => fraction(),
+        ProgressStyle::Both => format!("{:.1}% ({})", percent, fraction()),
+    }
+}
+
+/// Widest bar that fits a terminal `columns` wide next to `extra` columns of
+/// trailing text, so the line never wraps and breaks the in-place redraw.
+fn fit_bar_width(columns: usize, extra: usize) -> usize {
+    columns
+        .saturating_sub(BAR_LINE_CHROME + extra)
+        .clamp(MIN_BAR_WIDTH, MAX_BAR_WIDTH)
+}
+
+/// Save the window title on the terminal's title stack (XTWINOPS 22).
+const PUSH_TITLE: &str = "\x1B[22;0t";
+/// Restore the window title saved by `PUSH_TITLE` (XTWINOPS 23).
+const POP_TITLE: &str = "\x1B[23;0t";
+
+/// OSC 2 sequence setting the window title. Control characters are dropped so
+/// a timer name can't terminate the sequence early.
+fn title_sequence(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1B]2;{}\x07", title)
+}
+
+/// OSC 9;4 sequence removing the taskbar/tab progress indicator.
+const CLEAR_OS_PROGRESS: &str = "\x1B]9;4;0;0\x07";
+
+/// OSC 9;4 progress report (WezTerm, ConEmu, Windows Terminal) at `percent`,
+/// rounded to a whole number. Paused timers use the "paused" state.
+fn os_progress_sequence(percent: f64, paused: bool) -> String {
+    let state = if paused { 4 } else { 1 };
+    format!(
+        "\x1B]9;4;{};{}\x07",
+        state,
+        percent.round().clamp(0.0, 100.0) as u8
+    )
+}
+
+/// Restores the terminal when a display exits, however it exits.
+pub struct TerminalGuard {
+    raw_mode: bool,
+    /// Whether a full-screen display needs to leave the alternate screen.
+    alternate_screen: bool,
+    /// Whether the window title was pushed and needs popping.
+    restore_title: bool,
+    /// Whether an OSC 9;4 progress indicator needs clearing.
+    clear_os_progress: bool,
+    /// Whether the display was drawn on stderr rather than stdout, so that is
+    /// where the cursor and title are restored.
+    stderr: bool,
+}
+
+impl TerminalGuard {
+    /// Switch to raw mode and the alternate screen for a full-screen display.
+    /// Both are undone when the guard is dropped, including on an early `?`.
+    pub fn alternate_screen() -> io::Result<TerminalGuard> {
+        enable_raw_mode()?;
+        let guard = TerminalGuard {
+            raw_mode: true,
+            alternate_screen: true,
+            restore_title: false,
+            clear_os_progress: false,
+            stderr: false,
+        };
+        execute!(stdout(), EnterAlternateScreen)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.raw_mode {
+            let _ = disable_raw_mode();
+        }
+        if self.alternate_screen {
+            let _ = execute!(stdout(), LeaveAlternateScreen);
+        }
+        let mut out = progress_stream(self.stderr);
+        if self.restore_title {
+            let _ = write!(out, "{}", POP_TITLE);
+        }
+        if self.clear_os_progress {
+            let _ = write!(out, "{}", CLEAR_OS_PROGRESS);
+        }
+        let _ = write!(out, "\x1B[?25h");
+        let _ = out.flush();
+    }
+}
+
+/// The stream `run_timer` draws its progress on: stderr for
+/// `--progress-to-stderr`, so stdout only carries the completion line, and
+/// stdout otherwise.
+pub(crate) fn progress_stream(stderr: bool) -> Box<dyn Write> {
+    if stderr {
+        Box::new(io::stderr())
+    } else {
+        Box::new(stdout())
+    }
+}
+
+/// Whether the stream picked by `progress_stream` is a terminal.
+fn progress_is_terminal(stderr: bool) -> bool {
+    if stderr {
+        io::stderr().is_terminal()
+    } else {
+        is_interactive()
+    }
+}
+
+/// Convert a gradient sample to a terminal color, using the nearest xterm-256
+/// color on terminals without truecolor.
+fn rgb(color: colorgrad::Color) -> YansiColor {
+    let [r, g, b, _] = color.to_rgba8();
+    if supports_truecolor() {
+        YansiColor::Rgb(r, g, b)
+    } else {
+        YansiColor::Fixed(rgb_to_ansi256(r, g, b))
+    }
+}
+
+/// Color the spinner glyph for `frame` according to the theme.
+fn paint_spinner(theme: &ProgressBarTheme, spinner_chars: &[char], frame: usize) -> Painted<char> {
+    let glyph = spinner_chars[frame % spinner_chars.len()];
+    match theme {
+        ProgressBarTheme::Rainbow => {
+            let colors = [
+                YansiColor::Red,
+                YansiColor::Yellow,
+                YansiColor::Green,
+                YansiColor::Cyan,
+                YansiColor::Blue,
+                YansiColor::Magenta,
+            ];
+            Paint::new(glyph).fg(colors[(frame / 2) % colors.len()])
+        }
+        ProgressBarTheme::Gradient => Paint::new(glyph).fg(YansiColor::Cyan),
+        ProgressBarTheme::Color => Paint::new(glyph).fg(YansiColor::Cyan),
+        ProgressBarTheme::Plain => Paint::new(glyph),
+        ProgressBarTheme::Pulse => {
+            let colors = [YansiColor::Cyan, YansiColor::BrightCyan];
+            Paint::new(glyph).fg(colors[frame % colors.len()])
+        }
+        ProgressBarTheme::Custom(custom) => Paint::new(glyph).fg(rgb(custom.spinner)),
+    }
+}
+
+/// How wall-clock times (start, ETA, end) are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ClockFormat {
+    #[value(name = "12h")]
+    TwelveHour,
+    #[default]
+    #[value(name = "24h")]
+    TwentyFourHour,
+}
+
+impl ClockFormat {
+    fn pattern(self) -> &'static str {
+        match self {
+            ClockFormat::TwelveHour => "%I:%M:%S %p",
+            ClockFormat::TwentyFourHour => "%H:%M:%S",
+        }
+    }
+
+    /// Format a wall-clock time for display.
+    pub fn format(self, time: &DateTime<Local>) -> String {
+        time.format(self.pattern()).to_string()
+    }
+
+    /// The wall-clock time `remaining` from now, for "ends at" labels.
+    pub fn format_end(self, remaining: Duration) -> String {
+        let end = chrono::Duration::from_std(remaining)
+            .ok()
+            .and_then(|d| Local::now().checked_add_signed(d));
+        match end {
+            Some(end) => self.format(&end),
+            None => "--:--:--".to_string(),
+        }
+    }
+}
+
+/// How a `run_timer` call ended. Ctrl-C is reported as
+/// `TempusError::Interrupted` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerOutcome {
+    Completed,
+    /// Stopped early with `q` or Esc.
+    Stopped,
+}
+
+/// Set by the signal handler on Ctrl-C, SIGTERM or SIGHUP. Timers poll it so
+/// an interrupt unwinds back to `main`, restoring the terminal on the way and
+/// letting `main` pick the exit code, instead of exiting from the handler.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the signal handler, tolerating it already being installed so timers
+/// can be run back to back (e.g. interval rounds).
+pub fn set_interrupt_handler() -> Result<()> {
+    match ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+        Ok(()) | Err(ctrlc::Error::MultipleHandlers) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn check_interrupted() -> Result<()> {
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        Err(TempusError::Interrupted)
+    } else {
+        Ok(())
+    }
+}
+
+/// Sleep for `duration` in short steps, returning early with
+/// `TempusError::Interrupted` if the process is interrupted.
+fn sleep_interruptibly(duration: Duration) -> Result<()> {
+    let deadline = Instant::now() + duration;
+    loop {
+        check_interrupted()?;
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(());
+        }
+        sleep(left.min(Duration::from_millis(50)));
+    }
+}
+
+/// Time from now until `start`, or zero once it has passed.
+pub fn delay_until(start: DateTime<Local>) -> Duration {
+    (start - Local::now()).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// How often the `--start-at` waiting line counts down.
+const WAIT_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `--start-at`: hold off until `start`, showing how long is left on a line
+/// the timer then draws over. Ctrl-C cancels the wait like it would the
+/// timer, and a start that has already passed returns right away.
+pub fn wait_until(start: DateTime<Local>, options: &TimerOptions) -> Result<()> {
+    let start_str = options.clock_format.format(&start);
+    let show = options.format == OutputFormat::Text && !options.quiet;
+    let interactive = show && progress_is_terminal(options.progress_to_stderr);
+    let mut out = progress_stream(options.progress_to_stderr);
+    set_interrupt_handler()?;
+
+    if show && !interactive && !delay_until(start).is_zero() {
+        writeln!(out, "Waiting until {}...", start_str)?;
+    }
+
+    loop {
+        let remaining = delay_until(start);
+        if remaining.is_zero() {
+            break;
+        }
+        if interactive {
+            write!(
+                out,
+                "\x1B[?25l\r\x1B[KWaiting until {} ({} to go)",
+                start_str,
+                format_simple_duration(remaining)
+            )?;
+            out.flush()?;
+        }
+        if let Err(e) = sleep_interruptibly(remaining.min(WAIT_REDRAW_INTERVAL)) {
+            if interactive {
+                write!(out, "\r\x1B[K\x1B[?25h")?;
+            }
+            writeln!(out, "Timer interrupted.")?;
+            return Err(e);
+        }
+    }
+
+    if interactive {
+        write!(out, "\r\x1B[K\x1B[?25h")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// The instant a timer is counted from: now, moved back by `head_start`.
+fn backdated_start(head_start: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(head_start).unwrap_or(now)
+}
+
+/// Time a timer started at `start` has been running, leaving out
+/// `total_pause_duration` and, while paused, everything since `pause_time`.
+/// Saturates at zero rather than underflowing.
+pub(crate) fn running_time(
+    start: Instant,
+    pause_time: Option<Instant>,
+    total_pause_duration: Duration,
+) -> Duration {
+    pause_time
+        .unwrap_or_else(Instant::now)
+        .saturating_duration_since(start)
+        .saturating_sub(total_pause_duration)
+}
+
+/// Wall-clock counterpart of `backdated_start`, for the start time shown in
+/// the header.
+fn backdated_start_datetime(head_start: Duration) -> DateTime<Local> {
+    let now = SystemTime::now();
+    now.checked_sub(head_start).unwrap_or(now).into()
+}
+
+/// Pause between rings while an alarm waits to be acknowledged.
+const ALARM_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Keep flashing the screen and ringing the bell (or playing the sound) once
+/// a second after an alarm goes off, until a key is pressed. Does nothing
+/// when stdout is not a terminal, as nobody could acknowledge it.
+pub fn ring_until_acknowledged(options: &TimerOptions) -> Result<()> {
+    if !is_interactive() {
+        return Ok(());
+    }
+    set_interrupt_handler()?;
+    println!("Press any key to stop the alarm.");
+
+    let raw_mode = io::stdin().is_terminal() && enable_raw_mode().is_ok();
+    let _terminal_guard = TerminalGuard {
+        raw_mode,
+        alternate_screen: false,
+        restore_title: false,
+        clear_os_progress: false,
+        stderr: false,
+    };
+    let bell = BellOptions {
+        repeat: 1,
+        ..options.bell
+    };
+    loop {
+        check_interrupted()?;
+        flash_screen(|interval| {
+            sleep(interval);
+            true
+        });
+        completion_alert(&bell, options.sound.as_deref(), |_| true);
+        // A key pressed while flashing or ringing is still queued, so this
+        // returns straight away
+        if !sleep_unless_key_pressed(ALARM_INTERVAL) {
+            return Ok(());
+        }
+    }
+}
+
+pub fn run_timer(duration: Duration, name: &str, options: &TimerOptions) -> Result<TimerOutcome> {
+    let TimerOptions {
+        verbose,
+        bell,
+        notify,
+        clock_format,
+        chars,
+        ascii,
+        ref notification,
+        ref notify_at,
+        ref sound,
+        format,
+        ref status_file,
+        set_title,
+        os_progress,
+        ref log,
+        quiet,
+        warn_at,
+        ref complete_msg,
+        ref webhook,
+        progress_style,
+        bar_direction,
+        spinner,
+        head_start,
+        ref control_socket,
+        announce_every,
+        count_in,
+        compact,
+        progress_to_stderr,
+        ..
+    } = *options;
+    // Expanded once up front so the header, completion line, notification
+    // and history all show the same name
+    let name = &expand_name(name, &Local::now(), clock_format);
+    let spinner_chars: &[char] = if ascii {
+        &ASCII_SPINNER_CHARS
+    } else {
+        &SPINNER_CHARS
+    };
+
+    if format == OutputFormat::Json {
+        return run_json_timer(duration, name, options);
+    }
+
+    if quiet {
+        return run_quiet_timer(duration, name, options);
+    }
+
+    if !progress_is_terminal(progress_to_stderr) {
+        return run_plain_timer(duration, name, options);
+    }
+
+    // There is nothing to draw for a zero-length timer, so skip the terminal
+    // setup and go straight to the completion line and alerts
+    if duration.is_zero() {
+        return run_quiet_timer(duration, name, options);
+    }
+
+    // If NO_COLOR environment variable is set, override theme to Plain
+    let mut theme = options.theme.clone();
+    if !should_color(progress_is_terminal(progress_to_stderr)) {
+        theme = ProgressBarTheme::Plain;
+        yansi::disable();
+    } else {
+        yansi::enable();
+    }
+    let gradient = theme.gradient();
+
+    // Bound before touching the terminal, so a path in use fails cleanly
+    let control = control_socket
+        .as_deref()
+        .map(ControlSocket::bind)
+        .transpose()?;
+
+    // `add` over the control socket can extend the timer while it runs
+    let mut duration = duration;
+    let start_time = backdated_start(head_start);
+    let start_datetime = backdated_start_datetime(head_start);
+    let start_time_str = clock_format.format(&start_datetime);
+    let mut end_datetime = chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|d| start_datetime.checked_add_signed(d));
+
+    let mut out = progress_stream(progress_to_stderr);
+    write!(out, "\x1B[?25l")?; // hide cursor
+    out.flush()?;
+
+    // This will be updated in-place to show the progress bar. A compact line
+    // has no header above it, so it just redraws its own row.
+    if !compact {
+        writeln!(out)?;
+    }
+
+    // Raw mode lets us read single key presses for pause/quit. It is only
+    // available when stdin is a terminal, otherwise the timer just runs.
+    let raw_mode = io::stdin().is_terminal() && enable_raw_mode().is_ok();
+    if set_title {
+        write!(out, "{}", PUSH_TITLE)?;
+    }
+    let terminal_guard = TerminalGuard {
+        raw_mode,
+        alternate_screen: false,
+        restore_title: set_title,
+        clear_os_progress: os_progress,
+        stderr: progress_to_stderr,
+    };
+    let mut last_title = String::new();
+    let mut last_os_progress = String::new();
+
+    let mut status = StatusFile::new(status_file.as_deref());
+    set_interrupt_handler()?;
+
+    let mut spinner_idx = 0;
+    let mut pulse_offset = 0.0;
+    let pulse_speed = 0.2;
+
+    let mut pause_time: Option<Instant> = None;
+    let mut total_pause_duration = Duration::from_secs(0);
+    let mut quit = false;
+    let mut milestones = Milestones::new(notify_at);
+    let mut low_time = LowTimeWarning::new(warn_at);
+    let mut announcer = Announcer::new(announce_every, count_in);
+    let elapsed = |pause_time: Option<Instant>, total_pause_duration: Duration| {
+        running_time(start_time, pause_time, total_pause_duration)
+    };
+
+    while elapsed(pause_time, total_pause_duration) < duration {
+        if check_interrupted().is_err() {
+            status.remove();
+            drop(terminal_guard);
+            write!(out, "\r\x1B[K")?;
+            writeln!(out, "Timer interrupted.")?;
+            out.flush()?;
+            return Err(TempusError::Interrupted);
+        }
+
+        while let Some(request) = control.as_ref().and_then(ControlSocket::try_recv) {
+            match request.command {
+                ControlCommand::Status => {}
+                ControlCommand::Pause => {
+                    pause_time.get_or_insert_with(Instant::now);
+                }
+                ControlCommand::Resume => {
+                    if let Some(pause_start) = pause_time.take() {
+                        total_pause_duration += pause_start.elapsed();
+                    }
+                }
+                ControlCommand::Add(extra) => {
+                    duration += extra;
+                    end_datetime = end_datetime.and_then(|end_datetime| {
+                        end_datetime.checked_add_signed(chrono::Duration::from_std(extra).ok()?)
+                    });
+                }
+                ControlCommand::Quit => quit = true,
+            }
+
+            let elapsed = elapsed(pause_time, total_pause_duration);
+            let status = TimerStatus {
+                name,
+                remaining_secs: duration.saturating_sub(elapsed).as_secs_f64().ceil() as u64,
+                percent: (progress_ratio(elapsed, duration) * 1000.0)
+                    .round()
+                    .min(1000.0)
+                    / 10.0,
+                paused: pause_time.is_some(),
+            };
+            request.reply(serde_json::to_string(&status).map_err(io::Error::from)?);
+        }
+
+        if quit {
+            let remaining = duration.saturating_sub(elapsed(pause_time, total_pause_duration));
+            status.remove();
+            drop(terminal_guard);
+            write!(out, "\r\x1B[K")?;
+            writeln!(
+                out,
+                "{} stopped with {} remaining.",
+                Paint::new(name).bold(),
+                format_simple_duration(remaining)
+            )?;
+            out.flush()?;
+            return Ok(TimerOutcome::Stopped);
+        }
+
+        let elapsed = elapsed(pause_time, total_pause_duration);
+        let paused = pause_time.is_some();
+        let percent = (progress_ratio(elapsed, duration) * 100.0).min(100.0);
+
+        while let Some(milestone) = milestones.crossed(percent) {
+            let remaining = duration.saturating_sub(elapsed);
+            notify_milestone(name, milestone, remaining, notification);
+        }
+        status.update(duration.saturating_sub(elapsed), percent);
+
+        // Move up to the header and clear everything below it, so a resize that
+        // reflowed the previous frame doesn't leave stale rows behind
+        if compact {
+            write!(out, "\r\x1B[J")?;
+        } else {
+            write!(out, "\x1B[1A\r\x1B[J")?;
+        }
+
+        let remaining = duration.saturating_sub(elapsed);
+        let mut update_frequency = frame_interval(duration, remaining);
+        if control.is_some() {
+            update_frequency = update_frequency.min(control::POLL_INTERVAL);
+        }
+        if low_time.trigger(remaining) && bell.enabled {
+            write!(out, "\x07")?;
+        }
+        if let Some(secs) = announcer.crossed(remaining) {
+            announce_remaining(&mut out, options, secs);
+        }
+
+        if os_progress {
+            let sequence = os_progress_sequence(percent, paused);
+            if sequence != last_os_progress {
+                write!(out, "{}", sequence)?;
+                last_os_progress = sequence;
+            }
+        }
+
+        // Only re-send the title when the text changes, i.e. about once a second
+        if set_title {
+            let title = format!("{} {}", name, format_simple_duration(remaining));
+            if title != last_title {
+                write!(out, "{}", title_sequence(&title))?;
+                last_title = title;
+            }
+        }
+
+        let end_time = end_datetime.filter(|_| verbose).map(|end_datetime| {
+            // Pausing pushes the end back by however long the timer has been paused
+            let paused_for =
+                total_pause_duration + pause_time.map_or(Duration::ZERO, |p| p.elapsed());
+            let end_datetime = chrono::Duration::from_std(paused_for)
+                .ok()
+                .and_then(|d| end_datetime.checked_add_signed(d))
+                .unwrap_or(end_datetime);
+            clock_format.format(&end_datetime)
+        });
+
+        let verbose_width = if verbose {
+            format_simple_duration(remaining).len() + 3 + name.chars().count()
+        } else {
+            0
+        };
+        // Beyond the " 100.0%" already counted in `BAR_LINE_CHROME`
+        let readout_width = progress_text(progress_style, 100.0, elapsed, duration)
+            .len()
+            .saturating_sub("100.0%".len());
+        let columns = terminal::size().ok().map(|(columns, _)| columns as usize);
+        let bar_width = columns.map_or(MAX_BAR_WIDTH, |columns| {
+            fit_bar_width(columns, verbose_width + readout_width)
+        });
+
+        if let ProgressBarTheme::Pulse = theme {
+            pulse_offset += pulse_speed;
+            if pulse_offset > 1.0 {
+                pulse_offset = 0.0;
+            }
+        }
+
+        let frame = Frame {
+            name,
+            theme: &theme,
+            gradient: &gradient,
+            chars: &chars,
+            spinner,
+            spinner_chars,
+            start_time: &start_time_str,
+            end_time,
+            elapsed,
+            duration,
+            paused,
+            verbose,
+            warning: low_time.active(remaining),
+            progress_style,
+            bar_direction,
+            bar_width,
+            spinner_frame: spinner_idx,
+            pulse_offset,
+        };
+        if compact {
+            write!(
+                out,
+                "{}",
+                render_compact(&frame, columns.unwrap_or(COMPACT_WIDTH))
+            )?;
+        } else {
+            write!(out, "{}", render_frame(&frame))?;
+        }
+        spinner_idx = (spinner_idx + 1) % spinner_chars.len();
+
+        out.flush()?;
+
+        if !raw_mode {
+            // An interrupt cuts the sleep short and is handled at the top of the loop
+            let _ = sleep_interruptibly(update_frequency);
+            continue;
+        }
+
+        if event::poll(update_frequency)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('p') => match pause_time.take() {
+                    Some(pause_start) => total_pause_duration += pause_start.elapsed(),
+                    None => pause_time = Some(Instant::now()),
+                },
+                KeyCode::Char('q') | KeyCode::Esc => quit = true,
+                // Raw mode swallows SIGINT, so treat Ctrl-C like the signal handler does
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    INTERRUPTED.store(true, Ordering::SeqCst);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    status.remove();
+    drop(terminal_guard);
+    let total_elapsed = start_time.elapsed();
+
+    write!(out, "\r\x1B[K")?;
+    out.flush()?;
+    if bell.flash {
+        flash_screen(|interval| sleep_interruptibly(interval).is_ok());
+    }
+    // The bar may have been colored for stderr while stdout goes to a file
+    if progress_to_stderr && !should_use_color() {
+        yansi::disable();
+    }
+
+    let complete_color = match theme {
+        ProgressBarTheme::Plain => None,
+        ProgressBarTheme::Gradient | ProgressBarTheme::Custom(_) => Some(YansiColor::BrightGreen),
+        ProgressBarTheme::Rainbow => Some(YansiColor::BrightCyan),
+        ProgressBarTheme::Pulse => Some(YansiColor::BrightCyan),
+        ProgressBarTheme::Color => Some(YansiColor::BrightGreen),
+    };
+    let complete_paint = match complete_color {
+        Some(c) => Paint::new(name).bold().fg(c),
+        None => Paint::new(name).bold(),
+    };
+    println!(
+        "{}",
+        completion_message(
+            complete_msg.as_deref().unwrap_or(DEFAULT_COMPLETE_MSG),
+            &complete_paint.to_string(),
+            duration,
+            total_elapsed
+        )
+    );
+
+    // Ctrl-C is a signal again once raw mode is off, and cuts the alert short
+    completion_alert_to(&mut out, &bell, sound.as_deref(), |interval| {
+        sleep_interruptibly(interval).is_ok()
+    });
+    check_interrupted()?;
+
+    if notify && let Err(e) = send_notification(name, total_elapsed, notification) {
+        eprintln!("Warning: {}", e);
+    }
+    say_completion(options, name, duration, total_elapsed);
+    webhook_completion(webhook.as_deref(), name, total_elapsed);
+
+    log_completion(
+        log.as_deref(),
+        &HistoryEntry::new(start_datetime, name, duration, total_elapsed, "timer"),
+    );
+
+    Ok(TimerOutcome::Completed)
+}
+
+/// Fallback for when stdout is not a terminal: prints one plain status line
+/// per interval, with no colors or cursor movement, so logs stay readable.
+fn run_plain_timer(duration: Duration, name: &str, options: &TimerOptions) -> Result<TimerOutcome> {
+    let clock_format = options.clock_format;
+
+    let mut status = StatusFile::new(options.status_file.as_deref());
+    set_interrupt_handler()?;
+
+    let interval = if duration.as_secs() > 3600 {
+        Duration::from_secs(60)
+    } else if duration.as_secs() > 60 {
+        Duration::from_secs(10)
+    } else {
+        Duration::from_secs(1)
+    };
+
+    let start_time = backdated_start(options.head_start);
+    let start_datetime = backdated_start_datetime(options.head_start);
+    let start_time_str = clock_format.format(&start_datetime);
+    let mut milestones = Milestones::new(&options.notify_at);
+    let mut out = progress_stream(options.progress_to_stderr);
+
+    while start_time.elapsed() < duration {
+        let elapsed = start_time.elapsed();
+        let remaining = duration.saturating_sub(elapsed);
+        let percent = (elapsed.as_secs_f64() / duration.as_secs_f64() * 100.0).min(100.0);
+
+        while let Some(milestone) = milestones.crossed(percent) {
+            notify_milestone(name, milestone, remaining, &options.notification);
+        }
+        status.update(remaining, percent);
+
+        writeln!(
+            out,
+            "{} | {} | {} remaining | {:.1}%",
+            start_time_str,
+            name,
+            format_simple_duration(remaining),
+            percent
+        )?;
+        if let Err(e) = sleep_interruptibly(interval.min(remaining)) {
+            status.remove();
+            writeln!(out, "Timer interrupted.")?;
+            return Err(e);
+        }
+    }
+
+    status.remove();
+    let total_elapsed = start_time.elapsed();
+    println!(
+        "{}",
+        completion_message(
+            options
+                .complete_msg
+                .as_deref()
+                .unwrap_or(DEFAULT_COMPLETE_MSG),
+            name,
+            duration,
+            total_elapsed
+        )
+    );
+
+    // There is no terminal to ring, but a sound file can still be played
+    let no_bell = BellOptions {
+        enabled: false,
+        ..options.bell
+    };
+    completion_alert(&no_bell, options.sound.as_deref(), |_| true);
+
+    if options.notify
+        && let Err(e) = send_notification(name, total_elapsed, &options.notification)
+    {
+        eprintln!("Warning: {}", e);
+    }
+    say_completion(options, name, duration, total_elapsed);
+    webhook_completion(options.webhook.as_deref(), name, total_elapsed);
+
+    log_completion(
+        options.log.as_deref(),
+        &HistoryEntry::new(start_datetime, name, duration, total_elapsed, "timer"),
+    );
+
+    Ok(TimerOutcome::Completed)
+}
+
+/// `--quiet`: no status lines, colors or escape sequences, just a sleep for
+/// the whole duration followed by the plain completion line.
+fn run_quiet_timer(duration: Duration, name: &str, options: &TimerOptions) -> Result<TimerOutcome> {
+    set_interrupt_handler()?;
+
+    let start_time = backdated_start(options.head_start);
+    let start_datetime = backdated_start_datetime(options.head_start);
+    if let Err(e) = sleep_interruptibly(duration.saturating_sub(options.head_start)) {
+        writeln!(
+            progress_stream(options.progress_to_stderr),
+            "Timer interrupted."
+        )?;
+        return Err(e);
+    }
+    let total_elapsed = start_time.elapsed();
+
+    println!(
+        "{}",
+        completion_message(
+            options
+                .complete_msg
+                .as_deref()
+                .unwrap_or(DEFAULT_COMPLETE_MSG),
+            name,
+            duration,
+            total_elapsed
+        )
+    );
+
+    let mut out = progress_stream(options.progress_to_stderr);
+    completion_alert_to(
+        &mut out,
+        &options.bell,
+        options.sound.as_deref(),
+        |interval| sleep_interruptibly(interval).is_ok(),
+    );
+    check_interrupted()?;
+
+    if options.notify
+        && let Err(e) = send_notification(name, total_elapsed, &options.notification)
+    {
+        eprintln!("Warning: {}", e);
+    }
+    say_completion(options, name, duration, total_elapsed);
+    webhook_completion(options.webhook.as_deref(), name, total_elapsed);
+
+    log_completion(
+        options.log.as_deref(),
+        &HistoryEntry::new(start_datetime, name, duration, total_elapsed, "timer"),
+    );
+
+    Ok(TimerOutcome::Completed)
+}
+
+/// Fraction of `duration` that has elapsed. Not clamped, so it can briefly
+/// exceed 1.0 on the last frame.
+fn progress_ratio(elapsed: Duration, duration: Duration) -> f64 {
+    elapsed.as_millis() as f64 / duration.as_millis() as f64
+}
+
+/// Everything shown in one frame of `run_timer`: the header line and the bar
+/// line. It holds no terminal or clock state, so a frame can be rendered for
+/// any point in a timer.
+struct Frame<'a> {
+    name: &'a str,
+    theme: &'a ProgressBarTheme,
+    gradient: &'a colorgrad::LinearGradient,
+    chars: &'a BarChars,
+    /// Draw the spinner in front of the bar.
+    spinner: bool,
+    spinner_chars: &'a [char],
+    /// Formatted wall-clock start time.
+    start_time: &'a str,
+    /// Formatted end time, shown in the header when set.
+    end_time: Option<String>,
+    elapsed: Duration,
+    duration: Duration,
+    paused: bool,
+    /// Repeat the remaining time and name after the bar.
     verbose: bool,
-    mut theme: ProgressBarTheme,
-    bell: bool,
-    notify: bool,
-    use_12h: bool,
-) -> Result<()> {
-    // If NO_COLOR environment variable is set, override theme to Plain
-    if !should_use_color() {
-        theme = ProgressBarTheme::Plain;
-        yansi::disable();
-    } else {
-        yansi::enable();
-    }
+    /// `--warn-at` has been reached, so the header turns red.
+    warning: bool,
+    progress_style: ProgressStyle,
+    bar_direction: BarDirection,
+    bar_width: usize,
+    spinner_frame: usize,
+    pulse_offset: f64,
+}
+
+/// Render `frame` as its header and bar lines. The lines are separated by
+/// `\n\r` so the output also lines up in raw mode, and there are no cursor
+/// movement escapes, only colors.
+fn render_frame(frame: &Frame) -> String {
+    let remaining = frame.duration.saturating_sub(frame.elapsed);
+    let progress_ratio = progress_ratio(frame.elapsed, frame.duration);
+    let percent = (progress_ratio * 100.0).min(100.0);
 
-    let total_millis = duration.as_millis() as f64;
-    let start_time = Instant::now();
-    let start_system_time = SystemTime::now();
-    let start_datetime: DateTime<Local> = start_system_time.into();
-    let start_time_str = if use_12h {
-        start_datetime.format("%I:%M:%S %p").to_string()
+    // Running low overrides the theme's header color, even for Plain
+    let header_color = if frame.warning {
+        Some(YansiColor::Red)
     } else {
-        start_datetime.format("%H:%M:%S").to_string()
+        match frame.theme {
+            ProgressBarTheme::Plain => None,
+            _ => Some(YansiColor::BrightWhite),
+        }
+    };
+    let paint = |text: &str| match header_color {
+        Some(c) => Paint::new(text).fg(c).to_string(),
+        None => text.to_string(),
+    };
+    let name_paint = match header_color {
+        Some(c) => Paint::new(frame.name).bold().fg(c),
+        None => Paint::new(frame.name).bold(),
     };
 
-    print!("\x1B[?25l"); // hide cursor
-    stdout().flush()?;
+    let remaining_str = format_simple_duration(remaining);
+    let mut out = format!(
+        "{} | {} | {}{} remaining",
+        paint(frame.start_time),
+        name_paint,
+        if frame.paused { "PAUSED - " } else { "" },
+        paint(&remaining_str)
+    );
+    if let Some(end_time) = &frame.end_time {
+        out.push_str(&format!(" | ends {}", paint(end_time)));
+    }
 
-    // This will be updated in-place to show the progress bar
-    println!("");
+    out.push_str("\n\r");
+    if frame.spinner {
+        out.push_str(&format!(
+            "{} ",
+            paint_spinner(frame.theme, frame.spinner_chars, frame.spinner_frame)
+        ));
+    }
+    out.push_str(&format!(
+        "{}{}{} {}",
+        frame.chars.left,
+        render_bar(
+            frame.theme,
+            frame.gradient,
+            frame.chars,
+            frame.bar_direction.fill_ratio(progress_ratio),
+            frame.bar_width,
+            frame.pulse_offset
+        ),
+        frame.chars.right,
+        render_percent(
+            frame.theme,
+            frame.gradient,
+            percent,
+            &progress_text(frame.progress_style, percent, frame.elapsed, frame.duration)
+        )
+    ));
 
-    struct CursorGuard;
-    impl Drop for CursorGuard {
-        fn drop(&mut self) {
-            print!("\x1B[?25h");
-            let _ = stdout().flush();
-        }
+    if frame.verbose {
+        let time_str = format!("({})", remaining_str);
+        let time_paint = match frame.theme {
+            ProgressBarTheme::Plain => Paint::new(time_str),
+            _ => Paint::new(time_str).fg(YansiColor::BrightWhite),
+        };
+        out.push_str(&format!(" {}{}", time_paint, Paint::new(frame.name).bold()));
     }
-    let _cursor_guard = CursorGuard;
 
-    ctrlc::set_handler(move || {
-        print!("\r\x1B[K\x1B[?25h");
-        println!("Timer interrupted.");
-        std::process::exit(1);
-    })?;
+    out
+}
 
-    let update_frequency = if duration.as_secs() > 3600 {
-        Duration::from_millis(1000)
-    } else if duration.as_secs() > 60 {
-        Duration::from_millis(100)
+/// Line width for `--compact` when the terminal size is unknown.
+const COMPACT_WIDTH: usize = 60;
+/// Narrowest bar on a `--compact` line. The name is shortened before the bar
+/// goes below this, and the bar is only dropped once the name is down to its
+/// ellipsis.
+const MIN_COMPACT_BAR_WIDTH: usize = 5;
+
+/// Render `frame` as a single line at most `width` columns wide, for
+/// `--compact`: spinner, name, percentage, bar and remaining time. The start
+/// and end times are left out, and `frame.bar_width` is ignored in favor of
+/// whatever room is left over.
+fn render_compact(frame: &Frame, width: usize) -> String {
+    let remaining = frame.duration.saturating_sub(frame.elapsed);
+    let progress_ratio = progress_ratio(frame.elapsed, frame.duration);
+    let percent = (progress_ratio * 100.0).min(100.0);
+
+    let percent_str = format!("{:.0}%", percent.floor());
+    let remaining_str = format!(
+        "{}{}",
+        if frame.paused { "PAUSED " } else { "" },
+        format_simple_duration(remaining)
+    );
+    let spinner_width = if frame.spinner { 2 } else { 0 };
+    // The spaces after the name and the percentage, and before the remaining time
+    let fixed = spinner_width + percent_str.len() + remaining_str.len() + 3;
+    let room = width.saturating_sub(fixed);
+
+    let name_len = frame.name.chars().count();
+    let (name, bar_width) = if room >= name_len + MIN_COMPACT_BAR_WIDTH {
+        let bar_width = (room - name_len).min(MAX_BAR_WIDTH);
+        (frame.name.to_string(), bar_width)
+    } else if room > MIN_COMPACT_BAR_WIDTH {
+        (
+            truncate_with_ellipsis(frame.name, room - MIN_COMPACT_BAR_WIDTH),
+            MIN_COMPACT_BAR_WIDTH,
+        )
     } else {
-        Duration::from_millis(20)
+        (truncate_with_ellipsis(frame.name, room.max(1)), 0)
     };
 
-    let bar_width = 40;
-    let mut spinner_idx = 0;
-    let mut pulse_offset = 0.0;
-    let pulse_speed = 0.2;
+    let name_paint = match (frame.warning, frame.theme) {
+        (true, _) => Paint::new(name).bold().fg(YansiColor::Red),
+        (false, ProgressBarTheme::Plain) => Paint::new(name).bold(),
+        (false, _) => Paint::new(name).bold().fg(YansiColor::BrightWhite),
+    };
 
-    while start_time.elapsed() < duration {
-        let elapsed = start_time.elapsed();
-        let elapsed_millis = elapsed.as_millis() as f64;
+    let mut out = String::new();
+    if frame.spinner {
+        out.push_str(&format!(
+            "{} ",
+            paint_spinner(frame.theme, frame.spinner_chars, frame.spinner_frame)
+        ));
+    }
+    out.push_str(&format!(
+        "{} {}",
+        name_paint,
+        render_percent(frame.theme, frame.gradient, percent, &percent_str)
+    ));
+    if bar_width > 0 {
+        out.push(' ');
+        out.push_str(&render_bar(
+            frame.theme,
+            frame.gradient,
+            frame.chars,
+            frame.bar_direction.fill_ratio(progress_ratio),
+            bar_width,
+            frame.pulse_offset,
+        ));
+    }
+    out.push_str(&format!(" {}", remaining_str));
+    out
+}
 
-        let progress_ratio = elapsed_millis / total_millis;
-        let percent = (progress_ratio * 100.0).min(100.0);
+/// Shorten `text` to at most `max` characters, ending in `…` when anything
+/// was cut.
+fn truncate_with_ellipsis(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
 
-        print!("\x1B[1A\r\x1B[K"); // move cursor up one line
+/// Shortest and longest time between `run_timer` frames.
+const MIN_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+/// Remaining time from which `run_timer` always draws at `MIN_FRAME_INTERVAL`.
+const FINAL_STRETCH: Duration = Duration::from_secs(60);
 
-        // Display the header with start time, name, and remaining time
-        let remaining = duration
-            .checked_sub(elapsed)
-            .unwrap_or(Duration::from_secs(0));
+/// Time between `run_timer` frames: a 0.1% step of `duration`, clamped, or the fastest near the end.
+fn frame_interval(duration: Duration, remaining: Duration) -> Duration {
+    if remaining <= FINAL_STRETCH {
+        return MIN_FRAME_INTERVAL;
+    }
+    (duration / 1000).clamp(MIN_FRAME_INTERVAL, MAX_FRAME_INTERVAL)
+}
 
-        let header_color = match theme {
-            ProgressBarTheme::Plain => None,
-            _ => Some(YansiColor::BrightWhite),
-        };
+/// Which way the bar moves as time passes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BarDirection {
+    /// Empty at the start, filling up from the left.
+    #[default]
+    Fill,
+    /// Full at the start, emptying from the right.
+    Deplete,
+}
 
-        let start_time_paint = match header_color {
-            Some(c) => Paint::new(&start_time_str).fg(c),
-            None => Paint::new(&start_time_str),
-        };
+impl BarDirection {
+    /// Fraction of the bar that is filled at `progress_ratio`.
+    fn fill_ratio(self, progress_ratio: f64) -> f64 {
+        match self {
+            BarDirection::Fill => progress_ratio,
+            BarDirection::Deplete => 1.0 - progress_ratio,
+        }
+        .clamp(0.0, 1.0)
+    }
+}
 
-        let name_paint = match header_color {
-            Some(c) => Paint::new(name).bold().fg(c),
-            None => Paint::new(name).bold(),
-        };
+/// What a single cell of the bar shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BarCell {
+    Filled,
+    /// The cell at the edge of the filled part, as a partial block.
+    Partial(char),
+    Empty,
+}
 
-        let remaining_str = format_simple_duration(remaining);
-        let remaining_paint = match header_color {
-            Some(c) => Paint::new(&remaining_str).fg(c),
-            None => Paint::new(&remaining_str),
+/// Decide cell `i` of a bar `bar_width` cells wide with `fill_ratio` of it
+/// filled from the left. With a `partial` style, the cell at the edge shows
+/// how much of it is filled; otherwise any cell the filled part reaches into
+/// is full.
+fn bar_cell(i: usize, bar_width: usize, fill_ratio: f64, partial: Option<BarStyle>) -> BarCell {
+    let filled_cells = fill_ratio * bar_width as f64;
+    let Some(style) = partial else {
+        return if (i as f64) < filled_cells {
+            BarCell::Filled
+        } else {
+            BarCell::Empty
         };
+    };
 
-        print!(
-            "{} | {} | {} remaining",
-            start_time_paint, name_paint, remaining_paint
-        );
-
-        print!("\n\r\x1B[K"); // move cursor down one line and clear it
-
-        let spinner_paint = match theme {
-            ProgressBarTheme::Rainbow => {
-                let colors = [
-                    YansiColor::Red,
-                    YansiColor::Yellow,
-                    YansiColor::Green,
-                    YansiColor::Cyan,
-                    YansiColor::Blue,
-                    YansiColor::Magenta,
-                ];
-                Paint::new(SPINNER_CHARS[spinner_idx]).fg(colors[(spinner_idx / 2) % colors.len()])
-            }
-            ProgressBarTheme::Gradient => {
-                Paint::new(SPINNER_CHARS[spinner_idx]).fg(YansiColor::Cyan)
-            }
-            ProgressBarTheme::Color => Paint::new(SPINNER_CHARS[spinner_idx]).fg(YansiColor::Cyan),
-            ProgressBarTheme::Plain => Paint::new(SPINNER_CHARS[spinner_idx]),
-            ProgressBarTheme::Pulse => {
-                let colors = [YansiColor::Cyan, YansiColor::BrightCyan];
-                Paint::new(SPINNER_CHARS[spinner_idx]).fg(colors[spinner_idx % colors.len()])
-            }
-        };
-        print!("{} ", spinner_paint);
-        spinner_idx = (spinner_idx + 1) % SPINNER_CHARS.len();
-
-        print!("{}", LEFT_BRACKET);
-
-        match theme {
-            ProgressBarTheme::Gradient => {
-                let gradient: colorgrad::LinearGradient = colorgrad::GradientBuilder::new()
-                    .colors(&[
-                        colorgrad::Color::new(0.0, 1.0, 0.0, 1.0), // Green
-                        colorgrad::Color::new(1.0, 1.0, 0.0, 1.0), // Yellow
-                        colorgrad::Color::new(1.0, 0.0, 0.0, 1.0), // Red
-                    ])
-                    .build()
-                    .unwrap();
-                for i in 0..bar_width {
-                    let position = i as f64 / bar_width as f64;
-                    if position < progress_ratio {
-                        let rel_pos = position / progress_ratio.max(0.01);
-                        let color = gradient.at(rel_pos as f32).to_rgba8();
-                        let yansi_color = YansiColor::Rgb(color[0], color[1], color[2]);
-                        print!("{}", Paint::new(PROGRESS_CHARS[7]).fg(yansi_color));
-                    } else if i == (progress_ratio * bar_width as f64) as usize
-                        && progress_ratio < 1.0
-                    {
-                        let partial = (progress_ratio * bar_width as f64)
-                            - (progress_ratio * bar_width as f64).floor();
-                        let idx = (partial * (PROGRESS_CHARS.len() - 1) as f64).floor() as usize;
-                        let color = gradient.at(0.0).to_rgba8();
-                        let yansi_color = YansiColor::Rgb(color[0], color[1], color[2]);
-                        print!("{}", Paint::new(PROGRESS_CHARS[idx]).fg(yansi_color));
-                    } else {
-                        print!("{}", PROGRESS_CHARS[8]);
-                    }
-                }
-            }
-            ProgressBarTheme::Rainbow => {
-                for i in 0..bar_width {
-                    let position = i as f64 / bar_width as f64;
-
-                    if position < progress_ratio {
-                        let color_idx = (i * 6 / bar_width) % 6;
-                        let color = match color_idx {
-                            0 => YansiColor::Red,
-                            1 => YansiColor::Yellow,
-                            2 => YansiColor::Green,
-                            3 => YansiColor::Cyan,
-                            4 => YansiColor::Blue,
-                            _ => YansiColor::Magenta,
-                        };
-
-                        print!("{}", Paint::new(PROGRESS_CHARS[7]).fg(color));
-                    } else if i == (progress_ratio * bar_width as f64) as usize
-                        && progress_ratio < 1.0
-                    {
-                        let partial = (progress_ratio * bar_width as f64)
-                            - (progress_ratio * bar_width as f64).floor();
-                        let idx = (partial * (PROGRESS_CHARS.len() - 1) as f64).floor() as usize;
-                        print!(
-                            "{}",
-                            Paint::new(PROGRESS_CHARS[idx]).fg(YansiColor::BrightWhite)
-                        );
-                    } else {
-                        print!("{}", PROGRESS_CHARS[8]);
-                    }
-                }
-            }
-            ProgressBarTheme::Plain => {
-                for i in 0..bar_width {
-                    let position = i as f64 / bar_width as f64;
-
-                    if position < progress_ratio {
-                        print!("{}", PROGRESS_CHARS[7]);
-                    } else if i == (progress_ratio * bar_width as f64) as usize
-                        && progress_ratio < 1.0
-                    {
-                        let partial = (progress_ratio * bar_width as f64)
-                            - (progress_ratio * bar_width as f64).floor();
-                        let idx = (partial * (PROGRESS_CHARS.len() - 1) as f64).floor() as usize;
-                        print!("{}", PROGRESS_CHARS[idx]);
-                    } else {
-                        print!("{}", PROGRESS_CHARS[8]);
-                    }
-                }
-            }
-            ProgressBarTheme::Pulse => {
-                pulse_offset += pulse_speed;
-                if pulse_offset > 1.0 {
-                    pulse_offset = 0.0;
-                }
+    let whole_cells = filled_cells.floor() as usize;
+    if i < whole_cells {
+        BarCell::Filled
+    } else if i == whole_cells && fill_ratio < 1.0 {
+        let fraction = filled_cells - filled_cells.floor();
+        BarCell::Partial(style.partial_glyph(fraction))
+    } else {
+        BarCell::Empty
+    }
+}
 
-                for i in 0..bar_width {
-                    let position = i as f64 / bar_width as f64;
+/// Render the body of the progress bar (without brackets) for the given theme,
+/// with `fill_ratio` of it filled from the left. `gradient` is the theme's
+/// gradient, built once by the caller rather than on every frame.
+fn render_bar(
+    theme: &ProgressBarTheme,
+    gradient: &colorgrad::LinearGradient,
+    chars: &BarChars,
+    fill_ratio: f64,
+    bar_width: usize,
+    pulse_offset: f64,
+) -> String {
+    let mut bar = String::with_capacity(bar_width);
 
-                    if position < progress_ratio {
+    for i in 0..bar_width {
+        let position = i as f64 / bar_width as f64;
+        match bar_cell(
+            i,
+            bar_width,
+            fill_ratio,
+            chars.partial.then_some(chars.style),
+        ) {
+            BarCell::Filled => {
+                let color = match theme {
+                    ProgressBarTheme::Gradient | ProgressBarTheme::Custom(_) => {
+                        let rel_pos = position / fill_ratio.max(0.01);
+                        Some(rgb(gradient.at(rel_pos as f32)))
+                    }
+                    ProgressBarTheme::Rainbow => Some(match (i * 6 / bar_width) % 6 {
+                        0 => YansiColor::Red,
+                        1 => YansiColor::Yellow,
+                        2 => YansiColor::Green,
+                        3 => YansiColor::Cyan,
+                        4 => YansiColor::Blue,
+                        _ => YansiColor::Magenta,
+                    }),
+                    ProgressBarTheme::Plain => None,
+                    ProgressBarTheme::Pulse => {
                         let pulse_position = (position + pulse_offset) % 1.0;
                         let brightness = (pulse_position * PI).sin().abs();
 
-                        let color = if brightness > 0.7 {
+                        Some(if brightness > 0.7 {
                             YansiColor::BrightCyan
                         } else if brightness > 0.3 {
                             YansiColor::Cyan
                         } else {
                             YansiColor::Blue
-                        };
-
-                        print!("{}", Paint::new(PROGRESS_CHARS[7]).fg(color));
-                    } else if i == (progress_ratio * bar_width as f64) as usize
-                        && progress_ratio < 1.0
-                    {
-                        let partial = (progress_ratio * bar_width as f64)
-                            - (progress_ratio * bar_width as f64).floor();
-                        let idx = (partial * (PROGRESS_CHARS.len() - 1) as f64).floor() as usize;
-                        print!(
-                            "{}",
-                            Paint::new(PROGRESS_CHARS[idx]).fg(YansiColor::BrightBlue)
-                        );
-                    } else {
-                        print!("{}", PROGRESS_CHARS[8]);
+                        })
                     }
+                    ProgressBarTheme::Color => Some(if position < 0.33 {
+                        YansiColor::Green
+                    } else if position < 0.66 {
+                        YansiColor::Yellow
+                    } else {
+                        YansiColor::BrightRed
+                    }),
+                };
+                match color {
+                    Some(color) => bar.push_str(&Paint::new(chars.fill).fg(color).to_string()),
+                    None => bar.push(chars.fill),
                 }
             }
-            ProgressBarTheme::Color => {
-                for i in 0..bar_width {
-                    let position = i as f64 / bar_width as f64;
-
-                    if position < progress_ratio {
-                        let color = if position < 0.33 {
-                            YansiColor::Green
-                        } else if position < 0.66 {
-                            YansiColor::Yellow
-                        } else {
-                            YansiColor::BrightRed
-                        };
-
-                        print!("{}", Paint::new(PROGRESS_CHARS[7]).fg(color));
-                    } else if i == (progress_ratio * bar_width as f64) as usize
-                        && progress_ratio < 1.0
-                    {
-                        let partial = (progress_ratio * bar_width as f64)
-                            - (progress_ratio * bar_width as f64).floor();
-                        let idx = (partial * (PROGRESS_CHARS.len() - 1) as f64).floor() as usize;
-                        print!(
-                            "{}",
-                            Paint::new(PROGRESS_CHARS[idx]).fg(YansiColor::BrightGreen)
-                        );
-                    } else {
-                        print!("{}", PROGRESS_CHARS[8]);
+            BarCell::Partial(glyph) => {
+                let color = match theme {
+                    ProgressBarTheme::Gradient | ProgressBarTheme::Custom(_) => {
+                        Some(rgb(gradient.at(0.0)))
                     }
+                    ProgressBarTheme::Rainbow => Some(YansiColor::BrightWhite),
+                    ProgressBarTheme::Plain => None,
+                    ProgressBarTheme::Pulse => Some(YansiColor::BrightBlue),
+                    ProgressBarTheme::Color => Some(YansiColor::BrightGreen),
+                };
+                match color {
+                    Some(color) => bar.push_str(&Paint::new(glyph).fg(color).to_string()),
+                    None => bar.push(glyph),
                 }
             }
+            BarCell::Empty => bar.push(chars.empty),
         }
+    }
 
-        print!("{}", RIGHT_BRACKET);
+    bar
+}
 
-        let percent_color = match theme {
-            ProgressBarTheme::Plain => None,
-            ProgressBarTheme::Gradient => {
-                let gradient: colorgrad::LinearGradient = colorgrad::GradientBuilder::new()
-                    .colors(&[
-                        colorgrad::Color::new(0.0, 1.0, 0.0, 1.0), // Green
-                        colorgrad::Color::new(1.0, 1.0, 0.0, 1.0), // Yellow
-                        colorgrad::Color::new(1.0, 0.0, 0.0, 1.0), // Red
-                    ])
-                    .build()
-                    .unwrap();
-                let color = gradient.at((percent / 100.0) as f32).to_rgba8();
-                Some(YansiColor::Rgb(color[0], color[1], color[2]))
-            }
-            ProgressBarTheme::Color => {
-                // Keep the original "Gradient" behavior
-                if percent < 33.0 {
-                    Some(YansiColor::Green)
-                } else if percent < 66.0 {
-                    Some(YansiColor::Yellow)
-                } else {
-                    Some(YansiColor::BrightRed)
-                }
+/// Render the bold readout `text` shown next to the bar, colored for `percent`.
+fn render_percent(
+    theme: &ProgressBarTheme,
+    gradient: &colorgrad::LinearGradient,
+    percent: f64,
+    text: &str,
+) -> String {
+    match percent_color(theme, gradient, percent) {
+        Some(c) => Paint::new(text).bold().fg(c).to_string(),
+        None => Paint::new(text).bold().to_string(),
+    }
+}
+
+/// Color used for the percentage readout next to the bar.
+fn percent_color(
+    theme: &ProgressBarTheme,
+    gradient: &colorgrad::LinearGradient,
+    percent: f64,
+) -> Option<YansiColor> {
+    match theme {
+        ProgressBarTheme::Plain => None,
+        ProgressBarTheme::Gradient | ProgressBarTheme::Custom(_) => {
+            let color = gradient.at((percent / 100.0) as f32);
+            Some(rgb(color))
+        }
+        ProgressBarTheme::Color => {
+            // Keep the original "Gradient" behavior
+            if percent < 33.0 {
+                Some(YansiColor::Green)
+            } else if percent < 66.0 {
+                Some(YansiColor::Yellow)
+            } else {
+                Some(YansiColor::BrightRed)
             }
-            ProgressBarTheme::Rainbow => Some(YansiColor::BrightWhite),
-            ProgressBarTheme::Pulse => Some(YansiColor::BrightCyan),
-        };
-        let percent_str = format!("{:.1}%", percent);
-        let percent_paint = match percent_color {
-            Some(c) => Paint::new(percent_str).bold().fg(c),
-            None => Paint::new(percent_str).bold(),
-        };
-        print!(" {}", percent_paint);
-
-        if verbose {
-            let remaining = duration
-                .checked_sub(elapsed)
-                .unwrap_or(Duration::from_secs(0));
-            let time_color = match theme {
-                ProgressBarTheme::Plain => None,
-                _ => Some(YansiColor::BrightWhite),
-            };
-            let time_str = format!("({})", format_simple_duration(remaining));
-            let time_paint = match time_color {
-                Some(c) => Paint::new(time_str).fg(c),
-                None => Paint::new(time_str),
-            };
-            print!(" {}{}", time_paint, Paint::new(name).bold());
         }
-
-        stdout().flush()?;
-        sleep(update_frequency);
+        ProgressBarTheme::Rainbow => Some(YansiColor::BrightWhite),
+        ProgressBarTheme::Pulse => Some(YansiColor::BrightCyan),
     }
+}
 
-    let total_elapsed = start_time.elapsed();
-
-    if bell {
-        print!("\x07");
+/// Print a static, half-filled preview bar for every theme, followed by the
+/// configured custom theme if there is one.
+pub fn list_themes(custom: Option<ProgressBarTheme>) {
+    let use_color = should_use_color();
+    if use_color {
+        yansi::enable();
+    } else {
+        yansi::disable();
     }
 
-    print!("\r\x1B[K");
+    for theme in ProgressBarTheme::ALL.into_iter().chain(custom) {
+        let preview = if use_color {
+            &theme
+        } else {
+            &ProgressBarTheme::Plain
+        };
+        let gradient = preview.gradient();
+        println!(
+            "{:<10} {}{}{} {}",
+            theme.name(),
+            LEFT_BRACKET,
+            render_bar(
+                preview,
+                &gradient,
+                &BarChars::default(),
+                0.5,
+                MAX_BAR_WIDTH,
+                0.0
+            ),
+            RIGHT_BRACKET,
+            render_percent(preview, &gradient, 50.0, "50.0%")
+        );
+    }
+}
 
-    let complete_color = match theme {
-        ProgressBarTheme::Plain => None,
-        ProgressBarTheme::Gradient => Some(YansiColor::BrightGreen),
-        ProgressBarTheme::Rainbow => Some(YansiColor::BrightCyan),
-        ProgressBarTheme::Pulse => Some(YansiColor::BrightCyan),
-        ProgressBarTheme::Color => Some(YansiColor::BrightGreen),
-    };
-    let complete_paint = match complete_color {
-        Some(c) => Paint::new(name).bold().fg(c),
-        None => Paint::new(name).bold(),
-    };
-    println!(
-        "{} completed! (took {})",
-        complete_paint,
-        format_simple_duration(total_elapsed)
-    );
+/// Color of the big clock digits. Rainbow and pulse animate with `frame`
+/// (one per 100ms), every other theme follows `progress_color`.
+fn big_clock_color(
+    theme: &ProgressBarTheme,
+    gradient: &colorgrad::LinearGradient,
+    progress: f64,
+    frame: usize,
+) -> Color {
+    match theme {
+        ProgressBarTheme::Rainbow => rainbow_color(frame),
+        ProgressBarTheme::Pulse => pulse_color(frame),
+        _ => progress_color(theme, gradient, progress),
+    }
+}
 
-    if notify {
-        send_notification(name, total_elapsed)?;
+/// The time the big clock shows: what is left of `duration`, or with
+/// `show_elapsed` how much of it has passed, capped at `duration`.
+fn big_clock_shown(elapsed: Duration, duration: Duration, show_elapsed: bool) -> Duration {
+    if show_elapsed {
+        elapsed.min(duration)
+    } else {
+        duration.saturating_sub(elapsed)
     }
+}
 
-    Ok(())
+/// Digits for the big clock: `MM:SS`, or `HH:MM:SS` from an hour up.
+fn big_clock_text(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs >= 3600 {
+        format!(
+            "{:02}:{:02}:{:02}",
+            secs / 3600,
+            (secs % 3600) / 60,
+            secs % 60
+        )
+    } else {
+        format!("{:02}:{:02}", (secs % 3600) / 60, secs % 60)
+    }
 }
 
-pub fn run_big_clock(duration: Duration, name: &str, bell: bool) -> std::io::Result<()> {
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+pub fn run_big_clock(
+    duration: Duration,
+    name: &str,
+    options: &TimerOptions,
+) -> Result<TimerOutcome> {
+    // Completes without drawing, the same way as the regular timer
+    if duration.is_zero() {
+        return run_timer(duration, name, options);
+    }
+
+    let name = &expand_name(name, &Local::now(), options.clock_format);
+    let ascii = options.ascii;
+    let theme = if should_use_color() {
+        options.theme.clone()
+    } else {
+        ProgressBarTheme::Plain
+    };
+    let gradient = theme.gradient();
+    let mut frame: usize = 0;
+    set_interrupt_handler()?;
+    let terminal_guard = TerminalGuard::alternate_screen()?;
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
-    let start_time = Instant::now();
+    let start_time = backdated_start(options.head_start);
+    let start_datetime = backdated_start_datetime(options.head_start);
     let mut paused = false;
     let mut pause_time: Option<Instant> = None;
     let mut total_pause_duration = Duration::from_secs(0);
+    let mut show_elapsed = false;
+    let mut completed_after = None;
     loop {
+        // Leave the alternate screen below before reporting the interrupt
+        if check_interrupted().is_err() {
+            break;
+        }
         terminal.draw(|f| {
+            let rem = running_time(start_time, pause_time, total_pause_duration);
+            let big_time = big_clock_text(big_clock_shown(rem, duration, show_elapsed));
             let size = f.area();
+            // Room left for the digits inside the margin and the box, above
+            // the subtitle line
+            let frame_cells = 2 * SCREEN_MARGIN + 2;
+            let (font, scale) = fit_big_font(
+                &big_time,
+                options.font,
+                ascii,
+                size.width.saturating_sub(frame_cells),
+                size.height.saturating_sub(frame_cells + 1),
+            );
+            let big_lines = render_big_time(&big_time, font, ascii, scale);
+            let digit_rows = big_lines.len() as u16;
+            let digit_width = big_lines[0].chars().count() as u16;
+            // Digits and the subtitle line under them
+            if screen_layout(size, digit_width, digit_rows + 1) == ScreenLayout::Minimal {
+                let progress = progress_ratio(rem, duration).min(1.0);
+                let status = format!(
+                    "{} {}{}",
+                    big_time,
+                    if show_elapsed { "elapsed" } else { "remaining" },
+                    if paused { " (paused)" } else { "" }
+                );
+                let style = Style::default()
+                    .fg(big_clock_color(&theme, &gradient, progress, frame))
+                    .add_modifier(Modifier::BOLD);
+                render_minimal(f, size, name, Span::styled(status, style));
+                return;
+            }
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .margin(2)
+                .margin(SCREEN_MARGIN)
                 .constraints(
                     [
-                        Constraint::Percentage(40),
-                        Constraint::Length(7),
-                        Constraint::Percentage(40),
+                        Constraint::Fill(1),
+                        // Digits, the subtitle line and the two borders
+                        Constraint::Length(digit_rows + 3),
+                        Constraint::Fill(1),
                     ]
                     .as_ref(),
                 )
                 .split(size);
             let timer_area = chunks[1];
+            let remaining = if rem >= duration {
+                Duration::from_secs(0)
+            } else {
+                duration - rem
+            };
+            let title = if ascii {
+                format!(" {} ", name)
+            } else {
+                format!(" ⏲️ {} ", name)
+            };
             let block = Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::White))
                 .title(Span::styled(
-                    format!(" ⏲️ {} ", name),
+                    title,
                     Style::default()
                         .fg(Color::White)
                         .add_modifier(Modifier::BOLD),
-                ));
+                ))
+                .title_bottom(
+                    Line::from(if paused {
+                        " paused ".to_string()
+                    } else {
+                        format!(" ends at {} ", options.clock_format.format_end(remaining))
+                    })
+                    .right_aligned(),
+                );
             f.render_widget(block.clone(), timer_area);
             let inner_area = timer_area.inner(Margin {
                 vertical: 1,
                 horizontal: 1,
             });
-            let rem = if paused {
-                if let Some(pause_start) = pause_time {
-                    pause_start.duration_since(start_time) - total_pause_duration
-                } else {
-                    start_time.elapsed() - total_pause_duration
-                }
-            } else {
-                start_time.elapsed() - total_pause_duration
-            };
-            let remaining = if rem >= duration {
-                Duration::from_secs(0)
-            } else {
-                duration - rem
-            };
-            let big_time = if remaining.as_secs() >= 3600 {
-                format!(
-                    "{:02}:{:02}:{:02}",
-                    remaining.as_secs() / 3600,
-                    (remaining.as_secs() % 3600) / 60,
-                    remaining.as_secs() % 60
-                )
-            } else {
-                format!(
-                    "{:02}:{:02}",
-                    (remaining.as_secs() % 3600) / 60,
-                    remaining.as_secs() % 60
-                )
-            };
-            let big_lines = render_big_time(&big_time);
+            let inner_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(digit_rows), Constraint::Length(1)].as_ref())
+                .split(inner_area);
+            let progress = progress_ratio(rem, duration).min(1.0);
             let big_block = Paragraph::new(big_lines.join("\n"))
                 .alignment(Alignment::Center)
                 .style(
                     Style::default()
-                        .fg(Color::White)
+                        .fg(big_clock_color(&theme, &gradient, progress, frame))
                         .add_modifier(Modifier::BOLD),
                 );
-            f.render_widget(big_block, inner_area);
+            f.render_widget(big_block, inner_chunks[0]);
+            let subtitle = format!(
+                "{} | {}",
+                if show_elapsed { "elapsed" } else { "remaining" },
+                options.clock_format.format(&Local::now())
+            );
+            let subtitle_paragraph = Paragraph::new(subtitle)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::DarkGray));
+            f.render_widget(subtitle_paragraph, inner_chunks[1]);
         })?;
-        if event::poll(std::time::Duration::from_millis(100))? {
+        frame += 1;
+        if event::poll(ANIMATION_FRAME)? {
             if let Event::Key(key) = event::read()? {
                 match key.code {
                     KeyCode::Char('q') | KeyCode::Esc => break,
+                    // Raw mode swallows SIGINT, so Ctrl-C arrives as a key press
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        INTERRUPTED.store(true, Ordering::SeqCst);
+                        break;
+                    }
                     KeyCode::Char('p') => {
                         paused = !paused;
                         if paused {
@@ -501,27 +1923,445 @@ pub fn run_big_clock(duration: Duration, name: &str, bell: bool) -> std::io::Res
                         total_pause_duration = Duration::from_secs(0);
                         paused = false;
                     }
+                    KeyCode::Char('e') => show_elapsed = !show_elapsed,
                     _ => {}
                 }
             }
         }
-        let rem = if paused {
-            if let Some(pause_start) = pause_time {
-                pause_start.duration_since(start_time) - total_pause_duration
-            } else {
-                start_time.elapsed() - total_pause_duration
-            }
-        } else {
-            start_time.elapsed() - total_pause_duration
-        };
+        let rem = running_time(start_time, pause_time, total_pause_duration);
         if rem >= duration {
-            if bell {
-                print!("\x07");
+            let total_elapsed = start_time.elapsed();
+            log_completion(
+                options.log.as_deref(),
+                &HistoryEntry::new(start_datetime, name, duration, total_elapsed, "big"),
+            );
+            if options.bell.flash {
+                flash_screen(sleep_unless_key_pressed);
             }
+            completion_alert(
+                &options.bell,
+                options.sound.as_deref(),
+                sleep_unless_key_pressed,
+            );
+            if options.notify
+                && let Err(e) = send_notification(name, total_elapsed, &options.notification)
+            {
+                eprintln!("Warning: {}", e);
+            }
+            say_completion(options, name, duration, total_elapsed);
+            webhook_completion(options.webhook.as_deref(), name, total_elapsed);
+            completed_after = Some(total_elapsed);
             break;
         }
     }
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    Ok(())
+    drop(terminal_guard);
+    if let Err(e) = check_interrupted() {
+        writeln!(
+            progress_stream(options.progress_to_stderr),
+            "Timer interrupted."
+        )?;
+        return Err(e);
+    }
+    let Some(total_elapsed) = completed_after else {
+        return Ok(TimerOutcome::Stopped);
+    };
+    // The big clock has no completion line of its own, only the one asked for
+    if let Some(template) = &options.complete_msg {
+        println!(
+            "{}",
+            completion_message(template, name, duration, total_elapsed)
+        );
+    }
+    Ok(TimerOutcome::Completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a plain, uncolored `Work` frame with ASCII glyphs, `elapsed` of
+    /// `total` seconds in, and hand it to `render`.
+    fn with_frame<T>(
+        elapsed: u64,
+        total: u64,
+        bar_width: usize,
+        render: impl FnOnce(&mut Frame) -> T,
+    ) -> T {
+        yansi::disable();
+        let theme = ProgressBarTheme::Plain;
+        let gradient = theme.gradient();
+        let chars = BarChars::ascii();
+        let mut frame = Frame {
+            name: "Work",
+            theme: &theme,
+            gradient: &gradient,
+            chars: &chars,
+            spinner: false,
+            spinner_chars: &ASCII_SPINNER_CHARS,
+            start_time: "10:00:00",
+            end_time: None,
+            elapsed: Duration::from_secs(elapsed),
+            duration: Duration::from_secs(total),
+            paused: false,
+            verbose: false,
+            warning: false,
+            progress_style: ProgressStyle::Percent,
+            bar_direction: BarDirection::Fill,
+            bar_width,
+            spinner_frame: 0,
+            pulse_offset: 0.0,
+        };
+        render(&mut frame)
+    }
+
+    #[test]
+    fn frame_shows_header_bar_and_percentage() {
+        assert_eq!(
+            with_frame(30, 60, 10, |frame| render_frame(frame)),
+            "10:00:00 | Work | 30s remaining\n\r|#####-----| 50.0%"
+        );
+        assert_eq!(
+            with_frame(0, 60, 4, |frame| render_frame(frame)),
+            "10:00:00 | Work | 1m 0s remaining\n\r|----| 0.0%"
+        );
+        assert_eq!(
+            with_frame(60, 60, 4, |frame| render_frame(frame)),
+            "10:00:00 | Work | 0s remaining\n\r|####| 100.0%"
+        );
+    }
+
+    #[test]
+    fn frame_shows_pause_end_time_spinner_and_verbose_tail() {
+        let rendered = with_frame(15, 60, 4, |frame| {
+            frame.paused = true;
+            frame.end_time = Some("10:01:00".to_string());
+            frame.spinner = true;
+            frame.spinner_frame = 1;
+            frame.verbose = true;
+            frame.progress_style = ProgressStyle::Both;
+            render_frame(frame)
+        });
+        assert_eq!(
+            rendered,
+            "10:00:00 | Work | PAUSED - 45s remaining | ends 10:01:00\n\r\
+             - |#---| 25.0% (15s/1m 0s) (45s)Work"
+        );
+    }
+
+    #[test]
+    fn compact_frame_fits_one_line() {
+        assert_eq!(
+            with_frame(30, 60, 10, |frame| render_compact(frame, 30)),
+            "Work 50% #########-------- 30s"
+        );
+        assert_eq!(
+            with_frame(30, 60, 10, |frame| render_compact(frame, 30))
+                .chars()
+                .count(),
+            30
+        );
+    }
+
+    #[test]
+    fn compact_frame_shortens_the_name_before_dropping_the_bar() {
+        let rendered = with_frame(30, 60, 10, |frame| {
+            frame.name = "A rather long timer name";
+            render_compact(frame, 24)
+        });
+        assert_eq!(rendered, "A rather … 50% ###-- 30s");
+
+        let rendered = with_frame(30, 60, 10, |frame| {
+            frame.name = "A rather long timer name";
+            render_compact(frame, 12)
+        });
+        assert_eq!(rendered, "A … 50% 30s");
+    }
+
+    /// A plain ASCII bar `width` cells wide at `progress` in `direction`.
+    fn plain_bar(direction: BarDirection, progress: f64, width: usize) -> String {
+        yansi::disable();
+        let theme = ProgressBarTheme::Plain;
+        render_bar(
+            &theme,
+            &theme.gradient(),
+            &BarChars::ascii(),
+            direction.fill_ratio(progress),
+            width,
+            0.0,
+        )
+    }
+
+    #[test]
+    fn fill_bar_grows_from_the_left() {
+        assert_eq!(plain_bar(BarDirection::Fill, 0.0, 10), "----------");
+        assert_eq!(plain_bar(BarDirection::Fill, 0.5, 10), "#####-----");
+        assert_eq!(plain_bar(BarDirection::Fill, 1.0, 10), "##########");
+    }
+
+    #[test]
+    fn deplete_bar_starts_full_and_empties() {
+        assert_eq!(plain_bar(BarDirection::Deplete, 0.0, 10), "##########");
+        assert_eq!(plain_bar(BarDirection::Deplete, 0.5, 10), "#####-----");
+        assert_eq!(plain_bar(BarDirection::Deplete, 1.0, 10), "----------");
+    }
+
+    #[test]
+    fn fill_ratio_is_clamped() {
+        assert_eq!(BarDirection::Fill.fill_ratio(1.5), 1.0);
+        assert_eq!(BarDirection::Deplete.fill_ratio(1.5), 0.0);
+        assert_eq!(BarDirection::Deplete.fill_ratio(-0.5), 1.0);
+    }
+
+    #[test]
+    fn partial_cell_sits_at_the_edge_in_both_directions() {
+        use BarCell::{Empty, Filled, Partial};
+        let cells = |direction: BarDirection, progress: f64| -> Vec<BarCell> {
+            (0..4)
+                .map(|i| bar_cell(i, 4, direction.fill_ratio(progress), Some(BarStyle::Block)))
+                .collect()
+        };
+        assert_eq!(
+            cells(BarDirection::Fill, 0.0),
+            [Partial('▏'), Empty, Empty, Empty]
+        );
+        assert_eq!(
+            cells(BarDirection::Fill, 0.5),
+            [Filled, Filled, Partial('▏'), Empty]
+        );
+        assert_eq!(cells(BarDirection::Fill, 1.0), [Filled; 4]);
+        assert_eq!(cells(BarDirection::Deplete, 0.0), [Filled; 4]);
+        assert_eq!(
+            cells(BarDirection::Deplete, 0.5),
+            [Filled, Filled, Partial('▏'), Empty]
+        );
+        assert_eq!(
+            cells(BarDirection::Deplete, 1.0),
+            [Partial('▏'), Empty, Empty, Empty]
+        );
+    }
+
+    #[test]
+    fn braille_glyph_adds_a_dot_every_eighth() {
+        assert_eq!(braille_glyph(0.0), '⠀');
+        assert_eq!(braille_glyph(0.124), '⠀');
+        assert_eq!(braille_glyph(0.125), '⡀');
+        assert_eq!(braille_glyph(0.5), '⡇');
+        assert_eq!(braille_glyph(0.874), '⣧');
+        assert_eq!(braille_glyph(0.875), '⣷');
+        assert_eq!(braille_glyph(0.999), '⣷');
+        assert_eq!(braille_glyph(1.0), '⣿');
+    }
+
+    #[test]
+    fn braille_glyph_clamps_out_of_range_fractions() {
+        assert_eq!(braille_glyph(-0.5), '⠀');
+        assert_eq!(braille_glyph(1.5), '⣿');
+    }
+
+    #[test]
+    fn braille_bar_shows_the_partial_cell_in_dots() {
+        assert_eq!(
+            bar_cell(1, 4, 0.3125, Some(BarStyle::Braille)),
+            BarCell::Partial('⡄')
+        );
+        assert_eq!(
+            bar_cell(0, 4, 0.3125, Some(BarStyle::Braille)),
+            BarCell::Filled
+        );
+        assert_eq!(
+            bar_cell(2, 4, 0.3125, Some(BarStyle::Braille)),
+            BarCell::Empty
+        );
+    }
+
+    /// Every milestone `samples` fire, in order.
+    fn fired(milestones: &mut Milestones, samples: &[f64]) -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|&percent| {
+                std::iter::from_fn(|| milestones.crossed(percent)).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn milestones_fire_once_as_progress_passes_them() {
+        let mut milestones = Milestones::new(&[90, 50, 50]);
+        assert_eq!(
+            fired(
+                &mut milestones,
+                &[0.0, 49.9, 50.0, 50.1, 89.0, 90.0, 95.0, 100.0]
+            ),
+            [50, 90]
+        );
+    }
+
+    #[test]
+    fn milestones_jumped_over_still_fire_once_each() {
+        let mut milestones = Milestones::new(&[25, 50, 75]);
+        assert_eq!(fired(&mut milestones, &[10.0, 80.0]), [25, 50, 75]);
+        assert!(fired(&mut milestones, &[80.0, 100.0]).is_empty());
+
+        let mut milestones = Milestones::new(&[25, 50, 75]);
+        assert_eq!(fired(&mut milestones, &[60.0, 60.0, 100.0]), [25, 50, 75]);
+        assert!(fired(&mut Milestones::new(&[]), &[100.0]).is_empty());
+    }
+
+    #[test]
+    fn low_time_warning_rings_once_at_the_threshold() {
+        let mut warning = LowTimeWarning::new(Some(Duration::from_secs(10)));
+        assert!(!warning.trigger(Duration::from_secs(11)));
+        assert!(warning.trigger(Duration::from_secs(10)));
+        assert!(!warning.trigger(Duration::from_secs(9)));
+        assert!(!warning.trigger(Duration::ZERO));
+        assert!(warning.active(Duration::from_secs(5)));
+
+        let mut unset = LowTimeWarning::new(None);
+        assert!(!unset.trigger(Duration::ZERO));
+        assert!(!unset.active(Duration::ZERO));
+    }
+
+    /// What `announcer` calls out at each remaining time in `samples`.
+    fn announced(announcer: &mut Announcer, samples: &[f64]) -> Vec<Option<u64>> {
+        samples
+            .iter()
+            .map(|&secs| announcer.crossed(Duration::from_secs_f64(secs)))
+            .collect()
+    }
+
+    #[test]
+    fn announcer_calls_each_second_out_once_as_it_is_reached() {
+        let mut announcer = Announcer::new(Some(Duration::from_secs(10)), 3);
+        let expected = [
+            (25.0, None),
+            (20.5, None),
+            (20.0, Some(20)),
+            (19.9, None),
+            (10.1, None),
+            (9.9, Some(10)),
+            (3.5, None),
+            (3.0, Some(3)),
+            (2.2, None),
+            (2.0, Some(2)),
+            (1.0, Some(1)),
+            (0.5, None),
+            (0.0, None),
+        ];
+        for (secs, call) in expected {
+            assert_eq!(announced(&mut announcer, &[secs]), [call], "at {secs}s");
+        }
+    }
+
+    #[test]
+    fn announcer_skipped_seconds_call_out_the_lowest_wanted() {
+        let mut announcer = Announcer::new(Some(Duration::from_secs(10)), 3);
+        // 21 -> 8 skips 20 and 10; only 10 is still worth saying
+        assert_eq!(
+            announced(&mut announcer, &[21.0, 8.0, 4.0]),
+            [None, Some(10), None]
+        );
+        // 4 -> 0.5 skips the whole count-in
+        assert_eq!(announced(&mut announcer, &[0.5]), [Some(1)]);
+
+        // A sub-second interval still announces every second
+        let mut announcer = Announcer::new(Some(Duration::from_millis(300)), 0);
+        assert_eq!(
+            announced(&mut announcer, &[3.0, 2.0, 1.0]),
+            [None, Some(2), Some(1)]
+        );
+    }
+
+    #[test]
+    fn frame_interval_steps_a_thousandth_of_the_timer_within_bounds() {
+        let secs = Duration::from_secs;
+        // 10 minutes: 0.6s a frame
+        assert_eq!(
+            frame_interval(secs(600), secs(300)),
+            Duration::from_millis(600)
+        );
+        // Short timers are floored, long ones capped
+        assert_eq!(frame_interval(secs(20), secs(61)), MIN_FRAME_INTERVAL);
+        assert_eq!(
+            frame_interval(secs(86_400), secs(3_600)),
+            MAX_FRAME_INTERVAL
+        );
+        // The final minute always runs at the fastest rate
+        assert_eq!(frame_interval(secs(86_400), secs(61)), MAX_FRAME_INTERVAL);
+        assert_eq!(frame_interval(secs(86_400), secs(60)), MIN_FRAME_INTERVAL);
+        assert_eq!(
+            frame_interval(secs(86_400), Duration::ZERO),
+            MIN_FRAME_INTERVAL
+        );
+    }
+
+    #[test]
+    fn progress_text_formats_each_style() {
+        let elapsed = Duration::from_secs(12 * 60);
+        let total = Duration::from_secs(25 * 60);
+        assert_eq!(
+            progress_text(ProgressStyle::Percent, 48.0, elapsed, total),
+            "48.0%"
+        );
+        assert_eq!(
+            progress_text(ProgressStyle::Fraction, 48.0, elapsed, total),
+            "12m 0s/25m 0s"
+        );
+        assert_eq!(
+            progress_text(ProgressStyle::Both, 48.0, elapsed, total),
+            "48.0% (12m 0s/25m 0s)"
+        );
+        assert_eq!(
+            progress_text(ProgressStyle::Percent, 99.96, elapsed, total),
+            "100.0%"
+        );
+        // Overshooting the end still reads as the full duration
+        assert_eq!(
+            progress_text(
+                ProgressStyle::Fraction,
+                100.0,
+                total + Duration::from_secs(3),
+                total
+            ),
+            "25m 0s/25m 0s"
+        );
+    }
+
+    #[test]
+    fn title_sequence_is_osc_2_without_control_characters() {
+        assert_eq!(title_sequence("Tea 03:00"), "\x1B]2;Tea 03:00\x07");
+        assert_eq!(
+            title_sequence("a\x07b\x1B]0;evil\x1B\\c\n"),
+            "\x1B]2;ab]0;evil\\c\x07"
+        );
+        assert_eq!(title_sequence(""), "\x1B]2;\x07");
+    }
+
+    #[test]
+    fn os_progress_sequence_reports_state_and_whole_percent() {
+        assert_eq!(os_progress_sequence(42.4, false), "\x1B]9;4;1;42\x07");
+        assert_eq!(os_progress_sequence(42.5, true), "\x1B]9;4;4;43\x07");
+        assert_eq!(os_progress_sequence(0.0, false), "\x1B]9;4;1;0\x07");
+        assert_eq!(os_progress_sequence(100.0, false), "\x1B]9;4;1;100\x07");
+        assert_eq!(os_progress_sequence(100.4, true), "\x1B]9;4;4;100\x07");
+    }
+
+    #[test]
+    fn running_time_leaves_out_pauses_without_underflowing() {
+        let start = Instant::now();
+        let secs = Duration::from_secs;
+        let paused_at = start + secs(30);
+        assert_eq!(running_time(start, Some(paused_at), secs(10)), secs(20));
+        assert_eq!(
+            running_time(start, Some(paused_at), secs(45)),
+            Duration::ZERO
+        );
+        // A pause started before the timer did counts as no time at all
+        let before = start.checked_sub(secs(5)).unwrap_or(start);
+        assert_eq!(
+            running_time(start, Some(before), Duration::ZERO),
+            Duration::ZERO
+        );
+        assert!(running_time(start, None, Duration::ZERO) < secs(5));
+        assert_eq!(running_time(start, None, secs(3_600)), Duration::ZERO);
+    }
 }
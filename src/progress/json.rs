@@ -0,0 +1,100 @@
+use super::{
+    Milestones, StatusFile, TimerOptions, TimerOutcome, backdated_start, backdated_start_datetime,
+    notify_milestone, say_completion, set_interrupt_handler, sleep_interruptibly,
+};
+use crate::Result;
+use crate::history::{HistoryEntry, log_completion};
+use crate::utils::{BellOptions, completion_alert, send_notification};
+use crate::webhook::webhook_completion;
+use serde::Serialize;
+use std::io::{self, Write, stdout};
+use std::time::Duration;
+
+/// A snapshot of a running timer, printed as one line of `--format json`.
+#[derive(Debug, Serialize)]
+pub(super) struct TimerStatus<'a> {
+    pub(super) name: &'a str,
+    pub(super) remaining_secs: u64,
+    pub(super) percent: f64,
+    pub(super) paused: bool,
+}
+
+/// Machine-readable mode: prints one `TimerStatus` JSON object per second and
+/// nothing else on stdout, so the stream can be piped straight into `jq`.
+pub(super) fn run_json_timer(
+    duration: Duration,
+    name: &str,
+    options: &TimerOptions,
+) -> Result<TimerOutcome> {
+    let mut status = StatusFile::new(options.status_file.as_deref());
+    set_interrupt_handler()?;
+
+    let interval = Duration::from_secs(1);
+    let start_time = backdated_start(options.head_start);
+    let start_datetime = backdated_start_datetime(options.head_start);
+    let mut milestones = Milestones::new(&options.notify_at);
+
+    let print_status = |elapsed: Duration| -> Result<()> {
+        let percent = if duration.is_zero() {
+            100.0
+        } else {
+            (elapsed.as_secs_f64() / duration.as_secs_f64() * 100.0).min(100.0)
+        };
+        let status = TimerStatus {
+            name,
+            // Round up so a 2s timer starts at 2, not 1
+            remaining_secs: duration.saturating_sub(elapsed).as_secs_f64().ceil() as u64,
+            percent: (percent * 10.0).round() / 10.0,
+            paused: false,
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&status).map_err(io::Error::from)?
+        );
+        stdout().flush()?;
+        Ok(())
+    };
+
+    while start_time.elapsed() < duration {
+        let elapsed = start_time.elapsed();
+        let remaining = duration.saturating_sub(elapsed);
+        let percent = (elapsed.as_secs_f64() / duration.as_secs_f64() * 100.0).min(100.0);
+
+        while let Some(milestone) = milestones.crossed(percent) {
+            notify_milestone(name, milestone, remaining, &options.notification);
+        }
+        status.update(remaining, percent);
+
+        print_status(elapsed)?;
+        if let Err(e) = sleep_interruptibly(interval.min(remaining)) {
+            status.remove();
+            return Err(e);
+        }
+    }
+
+    status.remove();
+    let total_elapsed = start_time.elapsed();
+    print_status(duration)?;
+
+    // No bell: stdout belongs to the JSON stream
+    let no_bell = BellOptions {
+        enabled: false,
+        ..options.bell
+    };
+    completion_alert(&no_bell, options.sound.as_deref(), |_| true);
+
+    if options.notify
+        && let Err(e) = send_notification(name, total_elapsed, &options.notification)
+    {
+        eprintln!("Warning: {}", e);
+    }
+    say_completion(options, name, duration, total_elapsed);
+    webhook_completion(options.webhook.as_deref(), name, total_elapsed);
+
+    log_completion(
+        options.log.as_deref(),
+        &HistoryEntry::new(start_datetime, name, duration, total_elapsed, "timer"),
+    );
+
+    Ok(TimerOutcome::Completed)
+}
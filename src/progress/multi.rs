@@ -0,0 +1,246 @@
+use super::{
+    ASCII_SPINNER_CHARS, INTERRUPTED, MAX_BAR_WIDTH, ProgressBarTheme, SPINNER_CHARS,
+    TerminalGuard, TimerOptions, TimerOutcome, check_interrupted, fit_bar_width, frame_interval,
+    paint_spinner, progress_ratio, render_bar, render_percent, say_completion,
+    set_interrupt_handler, sleep_interruptibly,
+};
+use crate::history::{HistoryEntry, log_completion};
+use crate::utils::{
+    BellOptions, DEFAULT_COMPLETE_MSG, completion_alert, completion_message, flash_screen,
+    format_simple_duration, is_interactive, send_notification, should_use_color,
+};
+use crate::webhook::webhook_completion;
+use crate::{Result, TempusError};
+use chrono::Local;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal::{self, enable_raw_mode},
+};
+use std::io::{self, IsTerminal, Write, stdout};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use yansi::Paint;
+
+/// Run independent `(name, duration)` timers side by side, one bar per timer.
+/// A timer that finishes prints its completion line above the bars still
+/// running and fires its own notification; like `run_intervals`, the bell
+/// rings once for each and the full bell and sound are kept for the last one.
+pub fn run_multi(timers: &[(String, Duration)], options: &TimerOptions) -> Result<TimerOutcome> {
+    let interactive = is_interactive();
+    let mut theme = options.theme.clone();
+    if !interactive || !should_use_color() {
+        theme = ProgressBarTheme::Plain;
+        yansi::disable();
+    } else {
+        yansi::enable();
+    }
+    let gradient = theme.gradient();
+    let spinner_chars: &[char] = if options.ascii {
+        &ASCII_SPINNER_CHARS
+    } else {
+        &SPINNER_CHARS
+    };
+    let complete_msg = options
+        .complete_msg
+        .as_deref()
+        .unwrap_or(DEFAULT_COMPLETE_MSG);
+
+    // Pad names and remaining times to the longest, so the bars line up
+    let name_width = timers
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let remaining_width = timers
+        .iter()
+        .map(|(_, duration)| format_simple_duration(*duration).len())
+        .max()
+        .unwrap_or(0);
+
+    set_interrupt_handler()?;
+    let start_time = Instant::now();
+    let start_datetime = Local::now();
+
+    let raw_mode = interactive && io::stdin().is_terminal() && enable_raw_mode().is_ok();
+    let terminal_guard = interactive.then(|| {
+        print!("\x1B[?25l"); // hide cursor
+        TerminalGuard {
+            raw_mode,
+            alternate_screen: false,
+            restore_title: false,
+            clear_os_progress: false,
+            stderr: false,
+        }
+    });
+    let newline = if raw_mode { "\r\n" } else { "\n" };
+
+    let mut finished = vec![false; timers.len()];
+    let mut drawn_lines = 0;
+    let mut spinner_idx = 0;
+    let mut pulse_offset = 0.0;
+    let pulse_speed = 0.2;
+
+    while finished.contains(&false) {
+        let elapsed = start_time.elapsed();
+
+        // Move up to the first bar and clear the previous frame
+        if drawn_lines > 0 {
+            print!("\x1B[{}A\r\x1B[J", drawn_lines);
+            drawn_lines = 0;
+        }
+
+        if check_interrupted().is_err() {
+            drop(terminal_guard);
+            println!("Timers interrupted.");
+            return Err(TempusError::Interrupted);
+        }
+
+        for (i, (name, duration)) in timers.iter().enumerate() {
+            if finished[i] || elapsed < *duration {
+                continue;
+            }
+            finished[i] = true;
+
+            let name_paint = if interactive {
+                Paint::new(name.as_str()).bold()
+            } else {
+                Paint::new(name.as_str())
+            };
+            print!(
+                "{}{}",
+                completion_message(complete_msg, &name_paint.to_string(), *duration, elapsed),
+                newline
+            );
+            // The last one gets the full alert once the bars are gone
+            if interactive && options.bell.enabled && finished.contains(&false) {
+                print!("\x07");
+            }
+            stdout().flush()?;
+
+            if options.notify
+                && let Err(e) = send_notification(name, elapsed, &options.notification)
+            {
+                eprintln!("Warning: {}", e);
+            }
+            say_completion(options, name, *duration, elapsed);
+            webhook_completion(options.webhook.as_deref(), name, elapsed);
+            log_completion(
+                options.log.as_deref(),
+                &HistoryEntry::new(start_datetime, name, *duration, elapsed, "multi"),
+            );
+        }
+
+        let running = timers
+            .iter()
+            .zip(&finished)
+            .filter(|(_, finished)| !**finished)
+            .map(|(timer, _)| timer);
+
+        // Without a terminal there is nothing to draw, so just wait for the
+        // next timer to finish
+        let Some(update_frequency) = running
+            .clone()
+            .map(|(_, duration)| {
+                let remaining = duration.saturating_sub(elapsed);
+                if interactive {
+                    frame_interval(*duration, remaining)
+                } else {
+                    remaining
+                }
+            })
+            .min()
+        else {
+            break;
+        };
+
+        if interactive {
+            let bar_width = terminal::size().map_or(MAX_BAR_WIDTH, |(columns, _)| {
+                fit_bar_width(columns as usize, name_width + remaining_width + 2)
+            });
+
+            if let ProgressBarTheme::Pulse = theme {
+                pulse_offset += pulse_speed;
+                if pulse_offset > 1.0 {
+                    pulse_offset = 0.0;
+                }
+            }
+
+            for (name, duration) in running {
+                let ratio = progress_ratio(elapsed, *duration);
+                let percent = (ratio * 100.0).min(100.0);
+                let mut line = String::new();
+                if options.spinner {
+                    line.push_str(&format!(
+                        "{} ",
+                        paint_spinner(&theme, spinner_chars, spinner_idx)
+                    ));
+                }
+                line.push_str(&format!(
+                    "{:<name_width$} {}{}{} {} {:>remaining_width$}",
+                    name,
+                    options.chars.left,
+                    render_bar(
+                        &theme,
+                        &gradient,
+                        &options.chars,
+                        options.bar_direction.fill_ratio(ratio),
+                        bar_width,
+                        pulse_offset
+                    ),
+                    options.chars.right,
+                    render_percent(&theme, &gradient, percent, &format!("{:>5.1}%", percent)),
+                    format_simple_duration(duration.saturating_sub(elapsed)),
+                ));
+                print!("{}{}", line, newline);
+                drawn_lines += 1;
+            }
+            spinner_idx = (spinner_idx + 1) % spinner_chars.len();
+            stdout().flush()?;
+        }
+
+        if !raw_mode {
+            // An interrupt cuts the sleep short and is handled at the top of the loop
+            let _ = sleep_interruptibly(update_frequency);
+            continue;
+        }
+
+        if event::poll(update_frequency)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    print!("\x1B[{}A\r\x1B[J", drawn_lines);
+                    drop(terminal_guard);
+                    let left = finished.iter().filter(|finished| !**finished).count();
+                    println!(
+                        "Stopped with {} timer{} still running.",
+                        left,
+                        if left == 1 { "" } else { "s" }
+                    );
+                    return Ok(TimerOutcome::Stopped);
+                }
+                // Raw mode swallows SIGINT, so treat Ctrl-C like the signal handler does
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    INTERRUPTED.store(true, Ordering::SeqCst);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    drop(terminal_guard);
+    if interactive && options.bell.flash {
+        flash_screen(|interval| sleep_interruptibly(interval).is_ok());
+    }
+    let bell = BellOptions {
+        enabled: interactive && options.bell.enabled,
+        ..options.bell
+    };
+    completion_alert(&bell, options.sound.as_deref(), |interval| {
+        sleep_interruptibly(interval).is_ok()
+    });
+    check_interrupted()?;
+
+    Ok(TimerOutcome::Completed)
+}
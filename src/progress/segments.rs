@@ -0,0 +1,223 @@
+use super::{OutputFormat, TimerOptions, TimerOutcome, run_timer};
+use crate::Result;
+use crate::utils::{
+    BellOptions, DEFAULT_COMPLETE_MSG, completion_message, is_interactive, send_notification,
+};
+use crate::webhook::webhook_completion;
+use std::time::{Duration, Instant};
+use yansi::Paint;
+
+/// The ordered `(label, duration)` segments of an interval workout: `rounds`
+/// rounds of work, each followed by rest except the last. Zero-length rest is
+/// skipped entirely.
+pub fn interval_segments(work: Duration, rest: Duration, rounds: u32) -> Vec<(String, Duration)> {
+    let mut segments = Vec::new();
+    for round in 1..=rounds {
+        segments.push((format!("Round {}/{} — WORK", round, rounds), work));
+        if round < rounds && !rest.is_zero() {
+            segments.push((format!("Round {}/{} — REST", round, rounds), rest));
+        }
+    }
+    segments
+}
+
+/// Run interval `segments` back to back. Every transition rings the bell once;
+/// the configured bell, sound and notification fire when the last one ends.
+pub fn run_intervals(
+    name: &str,
+    segments: &[(String, Duration)],
+    options: &TimerOptions,
+) -> Result<TimerOutcome> {
+    let transition = TimerOptions {
+        bell: BellOptions {
+            repeat: 1,
+            ..options.bell
+        },
+        notify: false,
+        notify_at: Vec::new(),
+        sound: None,
+        webhook: None,
+        ..options.clone()
+    };
+    let last = TimerOptions {
+        notify: false,
+        webhook: None,
+        ..options.clone()
+    };
+
+    let options_for = |i: usize| {
+        if i + 1 == segments.len() {
+            &last
+        } else {
+            &transition
+        }
+    };
+    let Some(total_elapsed) = run_segments(name, segments, options_for)? else {
+        return Ok(TimerOutcome::Stopped);
+    };
+
+    if options.notify
+        && let Err(e) = send_notification(name, total_elapsed, &options.notification)
+    {
+        eprintln!("Warning: {}", e);
+    }
+    webhook_completion(options.webhook.as_deref(), name, total_elapsed);
+
+    Ok(TimerOutcome::Completed)
+}
+
+/// The ordered `(label, duration)` segments of a Pomodoro cycle: `rounds` work
+/// sessions separated by short breaks, with a long break after every
+/// `LONG_BREAK_EVERY`th session. No break follows the final session.
+pub fn pomodoro_segments(
+    work: Duration,
+    short_break: Duration,
+    long_break: Duration,
+    rounds: u32,
+) -> Vec<(String, Duration)> {
+    const LONG_BREAK_EVERY: u32 = 4;
+
+    let mut segments = Vec::new();
+    for round in 1..=rounds {
+        segments.push((format!("Pomodoro {}/{}", round, rounds), work));
+        if round == rounds {
+            break;
+        }
+        if round % LONG_BREAK_EVERY == 0 {
+            segments.push(("Long break".to_string(), long_break));
+        } else {
+            segments.push(("Short break".to_string(), short_break));
+        }
+    }
+    segments
+}
+
+/// Run a Pomodoro cycle. Unlike intervals, every transition gets the full
+/// completion treatment: bell, sound and notification.
+pub fn run_pomodoro(
+    segments: &[(String, Duration)],
+    options: &TimerOptions,
+) -> Result<TimerOutcome> {
+    match run_segments("Pomodoro cycle", segments, |_| options)? {
+        Some(_) => Ok(TimerOutcome::Completed),
+        None => Ok(TimerOutcome::Stopped),
+    }
+}
+
+/// Run `segments` back to back with `options_for(i)` for segment `i`, then
+/// print a completion line for the whole run. Returns the total time taken, or
+/// `None` if a segment was stopped early, which ends the run.
+fn run_segments<'a>(
+    name: &str,
+    segments: &[(String, Duration)],
+    options_for: impl Fn(usize) -> &'a TimerOptions,
+) -> Result<Option<Duration>> {
+    let start_time = Instant::now();
+
+    for (i, (label, duration)) in segments.iter().enumerate() {
+        if run_timer(*duration, label, options_for(i))? == TimerOutcome::Stopped {
+            return Ok(None);
+        }
+    }
+
+    let total_elapsed = start_time.elapsed();
+    if segments
+        .first()
+        .is_none_or(|_| options_for(0).format == OutputFormat::Text)
+    {
+        let name_paint = if is_interactive() {
+            Paint::new(name).bold()
+        } else {
+            Paint::new(name)
+        };
+        println!(
+            "{}",
+            completion_message(
+                options_for(0)
+                    .complete_msg
+                    .as_deref()
+                    .unwrap_or(DEFAULT_COMPLETE_MSG),
+                &name_paint.to_string(),
+                segments.iter().map(|(_, duration)| *duration).sum(),
+                total_elapsed
+            )
+        );
+    }
+
+    Ok(Some(total_elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The labels of `segments`, in order.
+    fn labels(segments: &[(String, Duration)]) -> Vec<&str> {
+        segments.iter().map(|(label, _)| label.as_str()).collect()
+    }
+
+    #[test]
+    fn interval_rounds_alternate_work_and_rest_without_a_trailing_rest() {
+        let (work, rest) = (Duration::from_secs(40), Duration::from_secs(20));
+        let segments = interval_segments(work, rest, 3);
+        assert_eq!(
+            labels(&segments),
+            [
+                "Round 1/3 — WORK",
+                "Round 1/3 — REST",
+                "Round 2/3 — WORK",
+                "Round 2/3 — REST",
+                "Round 3/3 — WORK"
+            ]
+        );
+        assert_eq!(
+            segments.iter().map(|&(_, d)| d).collect::<Vec<_>>(),
+            [work, rest, work, rest, work]
+        );
+
+        assert!(interval_segments(work, rest, 0).is_empty());
+        assert_eq!(
+            interval_segments(work, rest, 1),
+            [("Round 1/1 — WORK".to_string(), work)]
+        );
+        assert_eq!(
+            labels(&interval_segments(work, Duration::ZERO, 2)),
+            ["Round 1/2 — WORK", "Round 2/2 — WORK"]
+        );
+    }
+
+    #[test]
+    fn pomodoro_takes_a_long_break_after_every_fourth_session() {
+        let (work, short, long) = (
+            Duration::from_secs(25 * 60),
+            Duration::from_secs(5 * 60),
+            Duration::from_secs(15 * 60),
+        );
+        let segments = pomodoro_segments(work, short, long, 9);
+        let breaks: Vec<_> = segments.iter().skip(1).step_by(2).cloned().collect();
+        assert_eq!(segments.len(), 17);
+        assert_eq!(
+            labels(&breaks),
+            [
+                "Short break",
+                "Short break",
+                "Short break",
+                "Long break",
+                "Short break",
+                "Short break",
+                "Short break",
+                "Long break"
+            ]
+        );
+        assert_eq!(breaks[3].1, long);
+        assert_eq!(breaks[0].1, short);
+        assert_eq!(segments[16], ("Pomodoro 9/9".to_string(), work));
+
+        // Four sessions end on the fourth, with no long break after it
+        assert_eq!(
+            labels(&pomodoro_segments(work, short, long, 4)).last(),
+            Some(&"Pomodoro 4/4")
+        );
+        assert!(pomodoro_segments(work, short, long, 0).is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+use super::{
+    ASCII_SPINNER_CHARS, BarChars, INTERRUPTED, MAX_BAR_WIDTH, ProgressBarTheme, SPINNER_CHARS,
+    TerminalGuard, TimerOptions, fit_bar_width, paint_spinner, rgb, set_interrupt_handler,
+};
+use crate::Result;
+use crate::utils::{
+    BellOptions, completion_alert, format_simple_duration, is_interactive, send_notification,
+    should_use_color,
+};
+use crate::webhook::webhook_completion;
+use chrono::Local;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal::{self, enable_raw_mode},
+};
+use std::io::{self, IsTerminal, Write, stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use yansi::{Color as YansiColor, Paint};
+
+/// Count up from zero until stopped with `q`/Esc or Ctrl-C, then report the
+/// total elapsed time and fire the usual completion bell/notification.
+/// Space records a lap while the clock keeps running.
+pub fn run_stopwatch(name: &str, options: &TimerOptions) -> Result<()> {
+    let TimerOptions {
+        bell,
+        notify,
+        clock_format,
+        chars,
+        ascii,
+        ref notification,
+        ref sound,
+        ref webhook,
+        ..
+    } = *options;
+    let spinner_chars: &[char] = if ascii {
+        &ASCII_SPINNER_CHARS
+    } else {
+        &SPINNER_CHARS
+    };
+
+    // Ctrl-C is how a stopwatch is normally stopped, so it ends the loop
+    // instead of exiting the process
+    set_interrupt_handler()?;
+    let stop = &INTERRUPTED;
+
+    let start_time = Instant::now();
+    let start_time_str = clock_format.format(&Local::now());
+
+    if !is_interactive() {
+        return run_plain_stopwatch(name, options, start_time, &start_time_str, stop);
+    }
+
+    // If NO_COLOR environment variable is set, override theme to Plain
+    let mut theme = options.theme.clone();
+    if !should_use_color() {
+        theme = ProgressBarTheme::Plain;
+        yansi::disable();
+    } else {
+        yansi::enable();
+    }
+
+    print!("\x1B[?25l"); // hide cursor
+    println!();
+
+    let raw_mode = io::stdin().is_terminal() && enable_raw_mode().is_ok();
+    let terminal_guard = TerminalGuard {
+        raw_mode,
+        alternate_screen: false,
+        restore_title: false,
+        clear_os_progress: false,
+        stderr: false,
+    };
+
+    let update_frequency = Duration::from_millis(100);
+    let mut frame = 0;
+    let mut laps: Vec<Duration> = Vec::new();
+
+    while !stop.load(Ordering::SeqCst) {
+        let elapsed = start_time.elapsed();
+
+        print!("\x1B[1A\r\x1B[J");
+
+        let header_color = match theme {
+            ProgressBarTheme::Plain => None,
+            _ => Some(YansiColor::BrightWhite),
+        };
+        let elapsed_str = format_simple_duration(elapsed);
+        match header_color {
+            Some(c) => print!(
+                "{} | {} | {} elapsed",
+                Paint::new(&start_time_str).fg(c),
+                Paint::new(name).bold().fg(c),
+                Paint::new(&elapsed_str).fg(c)
+            ),
+            None => print!(
+                "{} | {} | {} elapsed",
+                start_time_str,
+                Paint::new(name).bold(),
+                elapsed_str
+            ),
+        }
+
+        print!("\n\r");
+
+        let bar_width = terminal::size().map_or(MAX_BAR_WIDTH, |(columns, _)| {
+            fit_bar_width(columns as usize, 0)
+        });
+        if options.spinner {
+            print!("{} ", paint_spinner(&theme, spinner_chars, frame));
+        }
+        print!(
+            "{}{}{}",
+            chars.left,
+            render_bounce(&theme, &chars, bar_width, frame),
+            chars.right
+        );
+        stdout().flush()?;
+        frame += 1;
+
+        if !raw_mode {
+            sleep(update_frequency);
+            continue;
+        }
+
+        if event::poll(update_frequency)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char(' ') => {
+                    let lap = start_time.elapsed();
+                    let split = lap.saturating_sub(laps.last().copied().unwrap_or_default());
+                    laps.push(lap);
+                    // Replace the live display with the lap line and leave two
+                    // fresh rows below it for the next frame to draw into
+                    print!(
+                        "\x1B[1A\r\x1B[JLap {}: {} (total {})\r\n\n",
+                        laps.len(),
+                        format_simple_duration(split),
+                        format_simple_duration(lap)
+                    );
+                }
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                // Raw mode swallows SIGINT, so Ctrl-C arrives as a key press
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                _ => {}
+            }
+        }
+    }
+
+    drop(terminal_guard);
+    let total_elapsed = start_time.elapsed();
+
+    print!("\r\x1B[K");
+    println!(
+        "{} stopped after {}.",
+        Paint::new(name).bold(),
+        format_simple_duration(total_elapsed)
+    );
+
+    if !laps.is_empty() {
+        // The time since the last lap counts as the final lap
+        laps.push(total_elapsed);
+        print_lap_summary(&laps);
+    }
+
+    completion_alert(&bell, sound.as_deref(), |interval| {
+        sleep(interval);
+        true
+    });
+
+    if notify && let Err(e) = send_notification(name, total_elapsed, notification) {
+        eprintln!("Warning: {}", e);
+    }
+    webhook_completion(webhook.as_deref(), name, total_elapsed);
+
+    Ok(())
+}
+
+/// Stopwatch counterpart of `run_plain_timer` for when stdout is not a terminal.
+fn run_plain_stopwatch(
+    name: &str,
+    options: &TimerOptions,
+    start_time: Instant,
+    start_time_str: &str,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let interval = Duration::from_secs(10);
+    let poll = Duration::from_millis(100);
+    let mut next_line = start_time;
+
+    while !stop.load(Ordering::SeqCst) {
+        if Instant::now() >= next_line {
+            println!(
+                "{} | {} | {} elapsed",
+                start_time_str,
+                name,
+                format_simple_duration(start_time.elapsed())
+            );
+            next_line += interval;
+        }
+        sleep(poll);
+    }
+
+    let total_elapsed = start_time.elapsed();
+    println!(
+        "{} stopped after {}.",
+        name,
+        format_simple_duration(total_elapsed)
+    );
+
+    // There is no terminal to ring, but a sound file can still be played
+    let no_bell = BellOptions {
+        enabled: false,
+        ..options.bell
+    };
+    completion_alert(&no_bell, options.sound.as_deref(), |_| true);
+
+    if options.notify
+        && let Err(e) = send_notification(name, total_elapsed, &options.notification)
+    {
+        eprintln!("Warning: {}", e);
+    }
+    webhook_completion(options.webhook.as_deref(), name, total_elapsed);
+
+    Ok(())
+}
+
+/// Width of the block that bounces between the brackets in the stopwatch bar.
+const BOUNCE_BLOCK_WIDTH: usize = 3;
+
+/// Render an indeterminate bar (without brackets): a short block moving back
+/// and forth one cell per frame, for when there is no total to show progress of.
+fn render_bounce(
+    theme: &ProgressBarTheme,
+    chars: &BarChars,
+    bar_width: usize,
+    frame: usize,
+) -> String {
+    let travel = bar_width.saturating_sub(BOUNCE_BLOCK_WIDTH);
+    let position = if travel == 0 {
+        0
+    } else {
+        let step = frame % (2 * travel);
+        if step <= travel {
+            step
+        } else {
+            2 * travel - step
+        }
+    };
+
+    let mut bar = String::with_capacity(bar_width);
+    for i in 0..bar_width {
+        if !(position..position + BOUNCE_BLOCK_WIDTH).contains(&i) {
+            bar.push(chars.empty);
+            continue;
+        }
+        let color = match theme {
+            ProgressBarTheme::Plain => None,
+            ProgressBarTheme::Rainbow => Some(match (i * 6 / bar_width.max(1)) % 6 {
+                0 => YansiColor::Red,
+                1 => YansiColor::Yellow,
+                2 => YansiColor::Green,
+                3 => YansiColor::Cyan,
+                4 => YansiColor::Blue,
+                _ => YansiColor::Magenta,
+            }),
+            ProgressBarTheme::Gradient | ProgressBarTheme::Color => Some(YansiColor::Green),
+            ProgressBarTheme::Pulse => Some(YansiColor::BrightCyan),
+            ProgressBarTheme::Custom(custom) => Some(rgb(custom.gradient[0])),
+        };
+        match color {
+            Some(c) => bar.push_str(&Paint::new(chars.fill).fg(c).to_string()),
+            None => bar.push(chars.fill),
+        }
+    }
+    bar
+}
+
+/// Turn lap boundaries (elapsed time at each capture, ascending) into
+/// `(split, cumulative)` pairs, where the split is the time since the previous lap.
+fn lap_splits(boundaries: &[Duration]) -> Vec<(Duration, Duration)> {
+    let mut previous = Duration::ZERO;
+    boundaries
+        .iter()
+        .map(|&cumulative| {
+            let split = cumulative.saturating_sub(previous);
+            previous = cumulative;
+            (split, cumulative)
+        })
+        .collect()
+}
+
+fn print_lap_summary(boundaries: &[Duration]) {
+    println!("Lap   Split        Total");
+    for (i, (split, cumulative)) in lap_splits(boundaries).into_iter().enumerate() {
+        println!(
+            "{:<5} {:<12} {}",
+            i + 1,
+            format_simple_duration(split),
+            format_simple_duration(cumulative)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lap_splits_measure_each_lap_from_the_previous_one() {
+        let secs = Duration::from_secs;
+        assert!(lap_splits(&[]).is_empty());
+        assert_eq!(lap_splits(&[secs(7)]), [(secs(7), secs(7))]);
+        assert_eq!(
+            lap_splits(&[secs(10), secs(25), secs(25), secs(40)]),
+            [
+                (secs(10), secs(10)),
+                (secs(15), secs(25)),
+                (secs(0), secs(25)),
+                (secs(15), secs(40))
+            ]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::utils::write_atomic;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// A countdown saved with `--persist`, so `tempus resume` can pick it up
+/// after the process is restarted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedCountdown {
+    pub name: String,
+    pub target: DateTime<Local>,
+    /// Start of the window given with `--from`, if any.
+    #[serde(default)]
+    pub from: Option<DateTime<Local>>,
+}
+
+/// Directory holding the saved countdowns: `$XDG_STATE_HOME/tempus`, falling
+/// back to `~/.local/state/tempus`.
+fn state_dir() -> Option<PathBuf> {
+    let state_home = env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+
+    Some(state_home.join("tempus"))
+}
+
+/// Check that `id` can be used as a file name, for clap's `value_parser`.
+pub fn parse_persist_id(id: &str) -> Result<String, String> {
+    if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) {
+        return Err(format!(
+            "'{}' can't be used as an ID, it must not be empty, start with '.' or contain slashes",
+            id
+        ));
+    }
+    Ok(id.to_string())
+}
+
+fn state_path(id: &str) -> io::Result<PathBuf> {
+    state_dir()
+        .map(|dir| dir.join(format!("{}.json", id)))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "neither XDG_STATE_HOME nor HOME is set",
+            )
+        })
+}
+
+/// Write the countdown to the state file for `id`, replacing any earlier one.
+pub fn save(id: &str, countdown: &PersistedCountdown) -> io::Result<()> {
+    let path = state_path(id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&path, &serde_json::to_string(countdown)?)
+}
+
+/// Read the countdown saved for `id`, or `None` if there isn't one.
+pub fn load(id: &str) -> io::Result<Option<PersistedCountdown>> {
+    let contents = match fs::read_to_string(state_path(id)?) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+/// Delete the state file for `id`. A file that is already gone is fine.
+pub fn remove(id: &str) -> io::Result<()> {
+    match fs::remove_file(state_path(id)?) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn countdown_survives_a_round_trip() {
+        let target = Local.with_ymd_and_hms(2030, 1, 2, 3, 4, 5).unwrap();
+        let from = Local.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+        for from in [None, Some(from)] {
+            let countdown = PersistedCountdown {
+                name: "Launch, \"v2\"".to_string(),
+                target,
+                from,
+            };
+            let json = serde_json::to_string(&countdown).unwrap();
+            let loaded: PersistedCountdown = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded.name, countdown.name);
+            assert_eq!(loaded.target, target);
+            assert_eq!(loaded.from, from);
+        }
+    }
+
+    #[test]
+    fn countdowns_saved_without_from_still_load() {
+        let loaded: PersistedCountdown =
+            serde_json::from_str(r#"{"name":"Launch","target":"2030-01-02T03:04:05+00:00"}"#)
+                .unwrap();
+        assert_eq!(loaded.name, "Launch");
+        assert_eq!(
+            loaded.target,
+            chrono::Utc.with_ymd_and_hms(2030, 1, 2, 3, 4, 5).unwrap()
+        );
+        assert_eq!(loaded.from, None);
+    }
+
+    #[test]
+    fn persist_ids_must_be_plain_file_names() {
+        assert_eq!(parse_persist_id("launch"), Ok("launch".to_string()));
+        for id in ["", ".hidden", "a/b", "a\\b"] {
+            assert!(parse_persist_id(id).is_err(), "{:?}", id);
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::progress::ProgressBarTheme;
+use crate::config::{Config, CustomThemeConfig};
+use crate::progress::{BarChars, ProgressBarTheme};
 use std::str::FromStr;
 
 /// Error type for theme parsing failures
@@ -13,6 +14,138 @@ impl std::fmt::Display for ThemeParseError {
 
 impl std::error::Error for ThemeParseError {}
 
+impl ProgressBarTheme {
+    /// Every theme, in the order they are listed to users.
+    pub const ALL: [ProgressBarTheme; 5] = [
+        ProgressBarTheme::Gradient,
+        ProgressBarTheme::Rainbow,
+        ProgressBarTheme::Pulse,
+        ProgressBarTheme::Color,
+        ProgressBarTheme::Plain,
+    ];
+
+    /// The name accepted by `--theme` for this theme.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgressBarTheme::Gradient => "gradient",
+            ProgressBarTheme::Rainbow => "rainbow",
+            ProgressBarTheme::Plain => "plain",
+            ProgressBarTheme::Pulse => "pulse",
+            ProgressBarTheme::Color => "color",
+            ProgressBarTheme::Custom(_) => "custom",
+        }
+    }
+
+    /// The color gradient sampled by the gradient-based themes: the custom
+    /// stops for `Custom`, green to yellow to red for everything else.
+    pub fn gradient(&self) -> colorgrad::LinearGradient {
+        let stops = match self {
+            ProgressBarTheme::Custom(custom) => custom.gradient.clone(),
+            _ => default_gradient_stops(),
+        };
+        colorgrad::GradientBuilder::new()
+            .colors(&stops)
+            .build()
+            .unwrap()
+    }
+}
+
+/// Colors for `--theme custom`, parsed from the `[theme.custom]` config table.
+#[derive(Debug, Clone)]
+pub struct CustomTheme {
+    /// At least two gradient stops, from the start of the bar to the end.
+    pub gradient: Vec<colorgrad::Color>,
+    pub spinner: colorgrad::Color,
+}
+
+impl CustomTheme {
+    /// Parse the configured colors, warning on stderr and falling back to the
+    /// built-in gradient and spinner color for anything that doesn't parse.
+    pub fn from_config(config: &CustomThemeConfig) -> Self {
+        let gradient = match parse_gradient(&config.gradient) {
+            Ok(stops) => stops,
+            Err(e) => {
+                eprintln!(
+                    "Warning: invalid [theme.custom] gradient ({}), using the built-in gradient",
+                    e
+                );
+                default_gradient_stops()
+            }
+        };
+
+        let spinner = match &config.spinner {
+            Some(hex) => parse_hex_color(hex).unwrap_or_else(|| {
+                eprintln!(
+                    "Warning: invalid [theme.custom] spinner color '{}', using cyan",
+                    hex
+                );
+                default_spinner_color()
+            }),
+            None => default_spinner_color(),
+        };
+
+        CustomTheme { gradient, spinner }
+    }
+}
+
+fn default_gradient_stops() -> Vec<colorgrad::Color> {
+    vec![
+        colorgrad::Color::new(0.0, 1.0, 0.0, 1.0), // Green
+        colorgrad::Color::new(1.0, 1.0, 0.0, 1.0), // Yellow
+        colorgrad::Color::new(1.0, 0.0, 0.0, 1.0), // Red
+    ]
+}
+
+/// Cyan, matching the spinner of the built-in gradient theme.
+fn default_spinner_color() -> colorgrad::Color {
+    colorgrad::Color::new(0.0, 1.0, 1.0, 1.0)
+}
+
+fn parse_gradient(stops: &[String]) -> Result<Vec<colorgrad::Color>, String> {
+    if stops.len() < 2 {
+        return Err("expected at least two colors".to_string());
+    }
+    stops
+        .iter()
+        .map(|hex| parse_hex_color(hex).ok_or_else(|| format!("'{}' is not a hex color", hex)))
+        .collect()
+}
+
+/// Parse a `#rrggbb` or `#rgb` hex color. The `#` is optional.
+pub fn parse_hex_color(hex: &str) -> Option<colorgrad::Color> {
+    let digits = hex.trim().strip_prefix('#').unwrap_or(hex.trim());
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize, width: usize| {
+        let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).ok()?;
+        // `#abc` is shorthand for `#aabbcc`
+        Some(if width == 1 { value * 17 } else { value })
+    };
+    let width = match digits.len() {
+        3 => 1,
+        6 => 2,
+        _ => return None,
+    };
+
+    Some(colorgrad::Color::from_rgba8(
+        channel(0, width)?,
+        channel(1, width)?,
+        channel(2, width)?,
+        255,
+    ))
+}
+
+/// The `custom` theme from the config's `[theme.custom]` table, if it has one.
+pub fn custom_theme(config: &Config) -> Option<ProgressBarTheme> {
+    config
+        .theme
+        .custom
+        .as_ref()
+        .map(|custom| ProgressBarTheme::Custom(CustomTheme::from_config(custom)))
+}
+
 impl FromStr for ProgressBarTheme {
     type Err = ThemeParseError;
 
@@ -28,7 +161,62 @@ impl FromStr for ProgressBarTheme {
     }
 }
 
-/// Parse a theme name into a ProgressBarTheme, defaulting to Gradient for unknown names.
+/// Parse a theme name into a ProgressBarTheme, warning on stderr and defaulting to
+/// Gradient for unknown names.
+///
+/// `custom` is read from the config file and also falls back to Gradient if
+/// there is no `[theme.custom]` table.
 pub fn parse_theme(theme_name: &str) -> ProgressBarTheme {
-    ProgressBarTheme::from_str(theme_name).unwrap_or(ProgressBarTheme::Gradient)
+    if theme_name.eq_ignore_ascii_case("custom") {
+        return custom_theme(&crate::config::load_config()).unwrap_or_else(|| {
+            eprintln!("Warning: no [theme.custom] table in the config, falling back to gradient");
+            ProgressBarTheme::Gradient
+        });
+    }
+
+    ProgressBarTheme::from_str(theme_name).unwrap_or_else(|_| {
+        eprintln!(
+            "Warning: unknown theme '{}', falling back to gradient",
+            theme_name
+        );
+        ProgressBarTheme::Gradient
+    })
+}
+
+impl FromStr for BarChars {
+    type Err = String;
+
+    /// Parse a spec like `fill=#,empty=-,left=[,right=]`. Keys may be given in
+    /// any order and omitted ones keep their default glyph.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = BarChars {
+            partial: false,
+            ..BarChars::default()
+        };
+
+        for entry in s.split(',').filter(|entry| !entry.is_empty()) {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=char, got '{}'", entry))?;
+            let mut value_chars = value.chars();
+            let glyph = match (value_chars.next(), value_chars.next()) {
+                (Some(glyph), None) => glyph,
+                _ => return Err(format!("'{}' must be a single character", key)),
+            };
+            match key.trim() {
+                "fill" => chars.fill = glyph,
+                "empty" => chars.empty = glyph,
+                "left" => chars.left = glyph,
+                "right" => chars.right = glyph,
+                other => {
+                    return Err(format!(
+                        "unknown key '{}' (expected fill, empty, left or right)",
+                        other
+                    ));
+                }
+            }
+        }
+
+        Ok(chars)
+    }
 }
@@ -1,70 +1,1237 @@
-use crate::Result;
-use std::time::Duration;
+use crate::progress::ClockFormat;
+use crate::{Result, TempusError};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use crossterm::event::{self, Event, KeyEventKind};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
 use std::{env, process::Command};
 
 pub fn format_simple_duration(duration: Duration) -> String {
     let total_secs = duration.as_secs();
-    let hours = total_secs / 3600;
+    let days = total_secs / 86400;
+    let hours = (total_secs % 86400) / 3600;
     let mins = (total_secs % 3600) / 60;
     let secs = total_secs % 60;
 
-    match (hours, mins) {
-        (h, _) if h > 0 => format!("{}h {}m {}s", hours, mins, secs),
-        (_, m) if m > 0 => format!("{}m {}s", mins, secs),
+    match (days, hours, mins) {
+        (d, _, _) if d > 0 => format!("{}d {}h {}m {}s", days, hours, mins, secs),
+        (_, h, _) if h > 0 => format!("{}h {}m {}s", hours, mins, secs),
+        (_, _, m) if m > 0 => format!("{}m {}s", mins, secs),
         _ => format!("{}s", secs),
     }
 }
 
+/// `duration` in whole words for text-to-speech, e.g. `1 hour 5 minutes`,
+/// leaving out the units that are zero.
+pub fn spoken_duration(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let parts: Vec<String> = [
+        (total_secs / 3600, "hour"),
+        ((total_secs % 3600) / 60, "minute"),
+        (total_secs % 60, "second"),
+    ]
+    .into_iter()
+    .filter(|(amount, _)| *amount > 0)
+    .map(|(amount, unit)| format!("{} {}{}", amount, unit, if amount == 1 { "" } else { "s" }))
+    .collect();
+
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Durations below this get tenths of a second in `format_precise_duration`.
+const PRECISE_BELOW: Duration = Duration::from_secs(10);
+
+/// Like `format_simple_duration`, but with tenths of a second for anything
+/// under ten seconds (`2.5s`), so a short timer doesn't read as `0s`.
+pub fn format_precise_duration(duration: Duration) -> String {
+    if duration >= PRECISE_BELOW {
+        return format_simple_duration(duration);
+    }
+    let secs = format!("{:.1}", duration.as_secs_f64());
+    format!("{}s", secs.strip_suffix(".0").unwrap_or(&secs))
+}
+
+/// Replace the contents of `path` atomically: write a temporary file next to
+/// it, then rename it into place, so readers never see a half-written file.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp_path);
+    })
+}
+
+/// Whether to print colors. `NO_COLOR` always turns them off, `FORCE_COLOR`
+/// (set to anything but `0`) turns them on, and otherwise colors are used
+/// when stdout is a terminal.
 pub fn should_use_color() -> bool {
-    env::var("NO_COLOR").is_err()
+    should_color(is_interactive())
 }
 
-#[cfg(target_os = "linux")]
-fn send_platform_notification(name: &str, duration_str: &str) -> Result<()> {
-    Command::new("notify-send")
-        .args([
-            &format!("{} completed!", name),
-            &format!("Duration: {}", duration_str),
-        ])
-        .spawn()?;
+/// `should_use_color` for output going somewhere other than stdout, which is
+/// colored by default when `terminal` is true.
+pub fn should_color(terminal: bool) -> bool {
+    if env::var("NO_COLOR").is_ok() {
+        return false;
+    }
+    match env::var("FORCE_COLOR") {
+        Ok(force) if force != "0" => true,
+        _ => terminal,
+    }
+}
+
+/// Whether the terminal advertises 24-bit color through `COLORTERM`. Anything
+/// else gets the nearest xterm-256 color instead, since many terminals clamp
+/// truecolor escapes to one of the basic 16.
+pub fn supports_truecolor() -> bool {
+    static TRUECOLOR: OnceLock<bool> = OnceLock::new();
+    *TRUECOLOR.get_or_init(|| {
+        matches!(
+            env::var("COLORTERM").as_deref(),
+            Ok("truecolor") | Ok("24bit")
+        )
+    })
+}
+
+/// Levels of each channel in the xterm-256 6x6x6 color cube (indices 16-231).
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Nearest xterm-256 palette index for an RGB color: the closest entry of the
+/// color cube or of the 24-step gray ramp (indices 232-255), whichever is nearer.
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |v: u8| match v {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (v - 35) / 40,
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+
+    // The gray ramp runs from 8 to 238 in steps of 10
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = if average > 238 {
+        23
+    } else {
+        average.saturating_sub(3) / 10
+    } as u8;
+    let gray = 8 + 10 * gray_index;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+
+    if distance((gray, gray, gray)) < distance(cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+/// Whether stdout is a terminal we can redraw in place. When it isn't (pipes,
+/// redirects, CI logs), cursor movement escapes would only garble the output.
+pub fn is_interactive() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Title and body of the completion notification, shared by every backend.
+fn notification_text(name: &str, duration_str: &str) -> (String, String) {
+    (
+        format!("{} completed!", name),
+        format!("Duration: {}", duration_str),
+    )
+}
+
+/// Play the audio file at `path` (WAV, OGG, MP3, ...) and wait for it to finish.
+#[cfg(feature = "sound")]
+pub fn play_sound(path: &Path) -> Result<()> {
+    use std::fmt::Display;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    let sound_error = |e: &dyn Display| TempusError::Sound(format!("{}: {}", path.display(), e));
+
+    // Open the file first so a bad path is reported without touching the audio device
+    let file = File::open(path).map_err(|e| sound_error(&e))?;
+    let (_stream, handle) = rodio::OutputStream::try_default().map_err(|e| sound_error(&e))?;
+    let sink = rodio::Sink::try_new(&handle).map_err(|e| sound_error(&e))?;
+    let source = rodio::Decoder::new(BufReader::new(file)).map_err(|e| sound_error(&e))?;
+    sink.append(source);
+    sink.sleep_until_end();
     Ok(())
 }
 
+#[cfg(not(feature = "sound"))]
+pub fn play_sound(path: &Path) -> Result<()> {
+    Err(TempusError::Sound(format!(
+        "{}: tempus was built without the `sound` feature",
+        path.display()
+    )))
+}
+
+/// How the terminal bell is rung when a timer completes.
+#[derive(Debug, Clone, Copy)]
+pub struct BellOptions {
+    pub enabled: bool,
+    /// Flash the screen before the completion line, as a visual bell.
+    pub flash: bool,
+    /// Number of times to ring.
+    pub repeat: u32,
+    /// Pause between consecutive rings.
+    pub interval: Duration,
+}
+
+/// Run `action` `count` times with `wait(interval)` in between. Stops early as
+/// soon as `wait` returns false.
+fn repeat_spaced(
+    count: u32,
+    interval: Duration,
+    mut action: impl FnMut(),
+    mut wait: impl FnMut(Duration) -> bool,
+) {
+    for i in 0..count {
+        if i > 0 && !wait(interval) {
+            break;
+        }
+        action();
+    }
+}
+
+/// Sleep for `interval`, but return false early if a key is pressed. Meant for
+/// raw mode, where Ctrl-C arrives as a key press rather than a signal.
+pub fn sleep_unless_key_pressed(interval: Duration) -> bool {
+    let deadline = Instant::now() + interval;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        match event::poll(left) {
+            Ok(true) => {
+                if let Ok(Event::Key(key)) = event::read()
+                    && key.kind == KeyEventKind::Press
+                {
+                    return false;
+                }
+            }
+            Ok(false) => return true,
+            Err(_) => {
+                sleep(left);
+                return true;
+            }
+        }
+    }
+}
+
+/// Reverse-video mode on and off (DECSCNM), which inverts the whole screen.
+const FLASH_ON: &str = "\x1B[?5h";
+const FLASH_OFF: &str = "\x1B[?5l";
+const FLASH_COUNT: u32 = 3;
+const FLASH_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Invert the screen a few times as a visual bell. Does nothing when stdout
+/// is not a terminal or `NO_COLOR` is set.
+///
+/// `wait` sleeps between the flashes and returns false to stop early; the
+/// screen is always left in normal video.
+pub fn flash_screen(wait: impl FnMut(Duration) -> bool) {
+    if !is_interactive() || !should_use_color() {
+        return;
+    }
+    flash_screen_to(&mut io::stdout(), wait);
+}
+
+/// `flash_screen`, writing the flashes to `out` whatever it is.
+fn flash_screen_to(out: &mut impl Write, mut wait: impl FnMut(Duration) -> bool) {
+    for i in 0..FLASH_COUNT {
+        if i > 0 && !wait(FLASH_INTERVAL) {
+            break;
+        }
+        let _ = write!(out, "{}", FLASH_ON);
+        let _ = out.flush();
+        let keep_going = wait(FLASH_INTERVAL);
+        let _ = write!(out, "{}", FLASH_OFF);
+        let _ = out.flush();
+        if !keep_going {
+            break;
+        }
+    }
+}
+
+/// Signal that a timer finished: play `sound` if one was given, and ring the
+/// terminal bell instead when there is no sound or it fails to play.
+///
+/// `wait` sleeps between bell repeats and returns false to silence the rest.
+pub fn completion_alert(
+    bell: &BellOptions,
+    sound: Option<&Path>,
+    wait: impl FnMut(Duration) -> bool,
+) {
+    completion_alert_to(&mut io::stdout(), bell, sound, wait);
+}
+
+/// `completion_alert`, ringing the bell on `out` rather than stdout.
+pub fn completion_alert_to(
+    out: &mut impl Write,
+    bell: &BellOptions,
+    sound: Option<&Path>,
+    wait: impl FnMut(Duration) -> bool,
+) {
+    if let Some(path) = sound
+        && play_sound(path).is_ok()
+    {
+        return;
+    }
+    if bell.enabled {
+        repeat_spaced(
+            bell.repeat,
+            bell.interval,
+            || {
+                let _ = write!(out, "\x07");
+                let _ = out.flush();
+            },
+            wait,
+        );
+    }
+}
+
+/// Urgency level of a desktop notification, as understood by `notify-send`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    fn as_str(self) -> &'static str {
+        match self {
+            Urgency::Low => "low",
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+/// How desktop notifications are delivered.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationOptions {
+    /// Shell command template run instead of the built-in notifier.
+    pub command: Option<String>,
+    pub urgency: Urgency,
+    /// How long the notification stays on screen; zero keeps it until it is
+    /// dismissed. `None` leaves it to the notification server.
+    pub timeout: Option<Duration>,
+}
+
+#[cfg(all(
+    feature = "native-notifications",
+    any(target_os = "linux", target_os = "macos")
+))]
+fn send_native_notification(
+    summary: &str,
+    body: &str,
+    options: &NotificationOptions,
+) -> Result<()> {
+    let mut notification = notify_rust::Notification::new();
+    notification.appname("tempus").summary(summary).body(body);
+    // macOS has no notion of urgency and ignores the timeout
+    #[cfg(target_os = "linux")]
+    notification.urgency(match options.urgency {
+        Urgency::Low => notify_rust::Urgency::Low,
+        Urgency::Normal => notify_rust::Urgency::Normal,
+        Urgency::Critical => notify_rust::Urgency::Critical,
+    });
+    if let Some(timeout) = options.timeout {
+        notification.timeout(timeout);
+    }
+    notification
+        .show()
+        .map(|_| ())
+        .map_err(|e| TempusError::Notification(e.to_string()))
+}
+
+/// Toasts only come in a short (~7s) and a long (~25s) duration, so the long
+/// one stands in for critical notifications and longer timeouts.
+#[cfg(any(target_os = "windows", test))]
+fn wants_long_toast(options: &NotificationOptions) -> bool {
+    options.urgency == Urgency::Critical
+        || options
+            .timeout
+            .is_some_and(|t| t.is_zero() || t > Duration::from_secs(7))
+}
+
+#[cfg(all(feature = "native-notifications", target_os = "windows"))]
+fn send_native_notification(
+    summary: &str,
+    body: &str,
+    options: &NotificationOptions,
+) -> Result<()> {
+    use tauri_winrt_notification::{Duration as ToastDuration, Toast};
+
+    // The toast XML is built and escaped by the crate, so the text can be
+    // passed along as it is
+    let duration = if wants_long_toast(options) {
+        ToastDuration::Long
+    } else {
+        ToastDuration::Short
+    };
+    Toast::new(Toast::POWERSHELL_APP_ID)
+        .title(summary)
+        .text1(body)
+        .duration(duration)
+        .show()
+        .map_err(|e| TempusError::Notification(e.to_string()))
+}
+
+#[cfg(not(all(
+    feature = "native-notifications",
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+)))]
+fn send_native_notification(
+    _summary: &str,
+    _body: &str,
+    _options: &NotificationOptions,
+) -> Result<()> {
+    Err(TempusError::Notification(
+        "native notifications are not available in this build".to_string(),
+    ))
+}
+
+fn spawn_notifier(program: &str, args: &[&str]) -> Result<()> {
+    Command::new(program)
+        .args(args)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| TempusError::Notification(format!("failed to run {}: {}", program, e)))
+}
+
+/// Arguments for `notify-send`. The urgency and timeout flags are only added
+/// when they differ from what `notify-send` does on its own.
+#[cfg(any(target_os = "linux", test))]
+fn notify_send_args(summary: &str, body: &str, options: &NotificationOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if options.urgency != Urgency::Normal {
+        args.push("-u".to_string());
+        args.push(options.urgency.as_str().to_string());
+    }
+    if let Some(timeout) = options.timeout {
+        args.push("-t".to_string());
+        args.push(timeout.as_millis().to_string());
+    }
+    args.push(summary.to_string());
+    args.push(body.to_string());
+    args
+}
+
+#[cfg(target_os = "linux")]
+fn send_platform_notification(
+    summary: &str,
+    body: &str,
+    options: &NotificationOptions,
+) -> Result<()> {
+    let args = notify_send_args(summary, body, options);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    spawn_notifier("notify-send", &args)
+}
+
+/// Arguments for `osascript`. The text is passed as arguments to the script
+/// rather than written into it, so quotes in a timer name can't end the
+/// AppleScript string early.
+#[cfg(any(target_os = "macos", test))]
+fn osascript_args<'a>(summary: &'a str, body: &'a str) -> [&'a str; 8] {
+    [
+        "-e",
+        "on run argv",
+        "-e",
+        "display notification (item 2 of argv) with title (item 1 of argv)",
+        "-e",
+        "end run",
+        summary,
+        body,
+    ]
+}
+
 #[cfg(target_os = "macos")]
-fn send_platform_notification(name: &str, duration_str: &str) -> Result<()> {
-    // This is lifted off Stackoverflow. I do not care if it works, but let me know if it doesn't
-    // and I might fix it.
-    Command::new("osascript")
-        .args([
-            "-e",
-            &format!(
-                "display notification \"Duration: {}\" with title \"{}\"",
-                duration_str,
-                format!("{} completed!", name)
-            ),
-        ])
-        .spawn()?;
-    Ok(())
+fn send_platform_notification(
+    summary: &str,
+    body: &str,
+    _options: &NotificationOptions,
+) -> Result<()> {
+    spawn_notifier("osascript", &osascript_args(summary, body))
 }
 
-#[cfg(target_os = "windows")]
-fn send_platform_notification(name: &str, duration_str: &str) -> Result<()> {
-    // Thank you Sky for the PS script. I wouldn't care about it otherwise.
-    let script = format!(
-        "powershell -Command \"[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); $toastXml = [xml] $template.GetXml(); $toastXml.GetElementsByTagName('text')[0].AppendChild($toastXml.CreateTextNode('{} completed!')) > $null; $toastXml.GetElementsByTagName('text')[1].AppendChild($toastXml.CreateTextNode('Duration: {}')) > $null; $toast = [Windows.UI.Notifications.ToastNotification]::new($toastXml); [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('Tempus').Show($toast);\"",
-        name, duration_str
+/// Quote `text` as a single-quoted PowerShell string. Nothing is special in
+/// one but the quote itself, which PowerShell also accepts in its curly forms,
+/// so each of those is doubled.
+#[cfg(any(target_os = "windows", test))]
+fn powershell_quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Escape `text` for use as XML character data.
+#[cfg(any(target_os = "windows", test))]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// The PowerShell script that shows a toast, used when the `native-notifications`
+/// feature is off or the toast couldn't be shown through WinRT directly. The
+/// toast XML is written out in full so the text only ever appears escaped
+/// inside a quoted string.
+#[cfg(any(target_os = "windows", test))]
+fn toast_script(summary: &str, body: &str, options: &NotificationOptions) -> String {
+    let duration = if wants_long_toast(options) {
+        "long"
+    } else {
+        "short"
+    };
+    let xml = format!(
+        "<toast duration=\"{}\"><visual><binding template=\"ToastText02\"><text id=\"1\">{}</text><text id=\"2\">{}</text></binding></visual></toast>",
+        duration,
+        xml_escape(summary),
+        xml_escape(body)
     );
-    Command::new("cmd").args(["/C", &script]).spawn()?;
+    format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] > $null; $toastXml = [Windows.Data.Xml.Dom.XmlDocument]::new(); $toastXml.LoadXml({}); $toast = [Windows.UI.Notifications.ToastNotification]::new($toastXml); [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('Tempus').Show($toast)",
+        powershell_quote(&xml)
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn send_platform_notification(
+    summary: &str,
+    body: &str,
+    options: &NotificationOptions,
+) -> Result<()> {
+    // PowerShell is run directly rather than through `cmd /C`, so the script
+    // reaches it as a single argument with no second layer of quoting
+    let script = toast_script(summary, body, options);
+    spawn_notifier("powershell", &["-NoProfile", "-Command", &script])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn send_platform_notification(
+    _summary: &str,
+    _body: &str,
+    _options: &NotificationOptions,
+) -> Result<()> {
+    // No-op for unsupported platforms
     Ok(())
 }
 
+fn spawn_speaker(program: &str, args: &[&str]) -> Result<()> {
+    Command::new(program)
+        .args(args)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| TempusError::Speech(format!("failed to run {}: {}", program, e)))
+}
+
+/// Text-to-speech programs tried in turn on Linux, each with its arguments.
+/// The text is always a single argument, never parsed by a shell.
+#[cfg(any(target_os = "linux", test))]
+fn linux_speakers(text: &str) -> [(&'static str, [&str; 1]); 2] {
+    [("spd-say", [text]), ("espeak", [text])]
+}
+
+/// Arguments for `say` on macOS.
+#[cfg(any(target_os = "macos", test))]
+fn say_args(text: &str) -> [&str; 1] {
+    [text]
+}
+
+/// The PowerShell script reading `text` aloud on Windows, with the text only
+/// ever inside a quoted string.
+#[cfg(any(target_os = "windows", test))]
+fn speech_script(text: &str) -> String {
+    format!(
+        "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak({})",
+        powershell_quote(text)
+    )
+}
+
+/// Read `text` aloud with the platform's text-to-speech, without waiting for
+/// it to finish.
+#[cfg(target_os = "linux")]
+pub fn speak(text: &str) -> Result<()> {
+    let [(program, args), (fallback, fallback_args)] = linux_speakers(text);
+    spawn_speaker(program, &args).or_else(|_| spawn_speaker(fallback, &fallback_args))
+}
+
+#[cfg(target_os = "macos")]
+pub fn speak(text: &str) -> Result<()> {
+    spawn_speaker("say", &say_args(text))
+}
+
+#[cfg(target_os = "windows")]
+pub fn speak(text: &str) -> Result<()> {
+    spawn_speaker(
+        "powershell",
+        &["-NoProfile", "-Command", &speech_script(text)],
+    )
+}
+
 #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-fn send_platform_notification(_name: &str, _duration_str: &str) -> Result<()> {
+pub fn speak(_text: &str) -> Result<()> {
     // No-op for unsupported platforms
     Ok(())
 }
 
-pub fn send_notification(name: &str, duration: Duration) -> Result<()> {
+/// Replace each `{key}` placeholder in `template` with its value. Unknown
+/// placeholders and stray braces are kept as they are, and values are
+/// inserted verbatim without being re-scanned, so a name that itself contains
+/// `{duration}` is left alone.
+pub fn fill_placeholders(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let filled = values.iter().find_map(|(key, value)| {
+            rest[1..]
+                .strip_prefix(key)
+                .and_then(|after| after.strip_prefix('}'))
+                .map(|after| (value, after))
+        });
+        if let Some((value, after)) = filled {
+            out.push_str(value);
+            rest = after;
+        } else {
+            out.push('{');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where a spot in a `--notify-cmd` template sits with respect to shell quoting.
+#[cfg(not(target_os = "windows"))]
+#[derive(Clone, Copy, PartialEq)]
+enum ShellQuote {
+    None,
+    Single,
+    Double,
+}
+
+/// Turn a `--notify-cmd` template into a script that reads `{name}` and
+/// `{duration}` from its positional parameters `$1` and `$2`, so the values
+/// themselves are never parsed by the shell. Each placeholder is quoted to
+/// suit the spot it's in, which keeps `"{name} done"`, `'{name}'` and a bare
+/// `{name}` all working as they read.
+#[cfg(not(target_os = "windows"))]
+fn notify_script(template: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut quote = ShellQuote::None;
+    let mut chars = template.char_indices();
+    while let Some((i, c)) = chars.next() {
+        let rest = &template[i..];
+        let param = [("{name}", "$1"), ("{duration}", "$2")]
+            .into_iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder));
+        if let Some((placeholder, param)) = param {
+            match quote {
+                ShellQuote::None => out.push_str(&format!("\"{}\"", param)),
+                ShellQuote::Double => out.push_str(param),
+                ShellQuote::Single => out.push_str(&format!("'\"{}\"'", param)),
+            }
+            // Skip the rest of the placeholder, which is all ASCII
+            for _ in 1..placeholder.len() {
+                chars.next();
+            }
+            continue;
+        }
+
+        out.push(c);
+        match (quote, c) {
+            (ShellQuote::None | ShellQuote::Double, '\\') => {
+                if let Some((_, escaped)) = chars.next() {
+                    out.push(escaped);
+                }
+            }
+            (ShellQuote::None, '\'') => quote = ShellQuote::Single,
+            (ShellQuote::None, '"') => quote = ShellQuote::Double,
+            (ShellQuote::Single, '\'') | (ShellQuote::Double, '"') => quote = ShellQuote::None,
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Expand `{date}`, `{time}` and `{datetime}` in a timer name with the time
+/// the timer started, e.g. `Backup {date}` becomes `Backup 2025-05-10`.
+/// Names without placeholders come back unchanged.
+pub fn expand_name(name: &str, start: &DateTime<Local>, clock_format: ClockFormat) -> String {
+    let date = start.format("%Y-%m-%d").to_string();
+    let time = clock_format.format(start);
+    fill_placeholders(
+        name,
+        &[
+            ("date", &date),
+            ("time", &time),
+            ("datetime", &format!("{} {}", date, time)),
+        ],
+    )
+}
+
+/// Completion line printed by the inline timers when `--complete-msg` isn't given.
+pub const DEFAULT_COMPLETE_MSG: &str = "{name} completed! (took {elapsed})";
+
+/// Fill in a `--complete-msg` template: `{name}`, `{duration}` (the time the
+/// timer was set for) and `{elapsed}` (how long it actually took, pauses
+/// included, with tenths of a second when it is short).
+pub fn completion_message(
+    template: &str,
+    name: &str,
+    duration: Duration,
+    elapsed: Duration,
+) -> String {
+    fill_placeholders(
+        template,
+        &[
+            ("name", name),
+            ("duration", &format_simple_duration(duration)),
+            ("elapsed", &format_precise_duration(elapsed)),
+        ],
+    )
+}
+
+/// The shell command for a `--notify-cmd` template. The name and duration
+/// are handed over out of band, never spliced into the command line itself.
+#[cfg(not(target_os = "windows"))]
+fn notify_command(template: &str, name: &str, duration_str: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", &notify_script(template), "sh", name, duration_str]);
+    command
+}
+
+/// On Windows the values go through environment variables read with delayed
+/// expansion (`!VAR!`), which `cmd` only does after it has parsed the line.
+#[cfg(target_os = "windows")]
+fn notify_command(template: &str, name: &str, duration_str: &str) -> Command {
+    let line = fill_placeholders(
+        template,
+        &[
+            ("name", "!TEMPUS_NOTIFY_NAME!"),
+            ("duration", "!TEMPUS_NOTIFY_DURATION!"),
+        ],
+    );
+    let mut command = Command::new("cmd");
+    command
+        .args(["/V:ON", "/C", &line])
+        .env("TEMPUS_NOTIFY_NAME", name)
+        .env("TEMPUS_NOTIFY_DURATION", duration_str);
+    command
+}
+
+/// Run a `--notify-cmd` template and wait for it, so a failing command can
+/// be reported. Only for completion, once the timer has stopped drawing.
+fn run_notify_command(template: &str, name: &str, duration_str: &str) -> Result<()> {
+    notify_command_status(notify_command(template, name, duration_str).status())
+}
+
+/// Start a `--notify-cmd` template without waiting for it, for heads-ups sent
+/// while a timer is still running, so a slow command can't stall it. The
+/// command is reaped in the background and its exit status is ignored.
+fn spawn_notify_command(template: &str, name: &str, duration_str: &str) -> Result<()> {
+    let mut child = notify_command(template, name, duration_str)
+        .spawn()
+        .map_err(|e| TempusError::Notification(format!("failed to run notify command: {}", e)))?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+fn notify_command_status(status: std::io::Result<std::process::ExitStatus>) -> Result<()> {
+    let status = status
+        .map_err(|e| TempusError::Notification(format!("failed to run notify command: {}", e)))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(TempusError::Notification(format!(
+            "notify command exited with {}",
+            status
+        )))
+    }
+}
+
+/// Send a desktop notification that `name` finished after `duration`.
+///
+/// When a `--notify-cmd` template is given it is run through the shell instead.
+/// Otherwise this uses `notify-rust` when the `native-notifications` feature is
+/// enabled and falls back to the platform's command-line notifier if that fails.
+pub fn send_notification(
+    name: &str,
+    duration: Duration,
+    options: &NotificationOptions,
+) -> Result<()> {
     let duration_str = format_simple_duration(duration);
-    send_platform_notification(name, &duration_str)
+    let (summary, body) = notification_text(name, &duration_str);
+    deliver_notification(&summary, &body, name, &duration_str, options, true)
+}
+
+/// Send a heads-up that `name` has reached `percent`% with `remaining` to go.
+///
+/// A `--notify-cmd` template sees `{name}` as e.g. "Timer 90%" and
+/// `{duration}` as the time remaining.
+pub fn send_milestone_notification(
+    name: &str,
+    percent: u8,
+    remaining: Duration,
+    options: &NotificationOptions,
+) -> Result<()> {
+    let label = format!("{} {}%", name, percent);
+    let remaining_str = format_simple_duration(remaining);
+    let body = format!("{} remaining", remaining_str);
+    deliver_notification(&label, &body, &label, &remaining_str, options, false)
+}
+
+/// Send a heads-up that `name` has `remaining` to go, for focus mode's
+/// low-time warning.
+///
+/// A `--notify-cmd` template sees `{duration}` as the time remaining.
+pub fn send_remaining_notification(
+    name: &str,
+    remaining: Duration,
+    options: &NotificationOptions,
+) -> Result<()> {
+    let remaining_str = format_simple_duration(remaining);
+    let body = format!("{} remaining", remaining_str);
+    deliver_notification(name, &body, name, &remaining_str, options, false)
+}
+
+/// Deliver one notification. With `wait`, a `--notify-cmd` is run to
+/// completion; otherwise it is only started.
+fn deliver_notification(
+    summary: &str,
+    body: &str,
+    name: &str,
+    duration_str: &str,
+    options: &NotificationOptions,
+    wait: bool,
+) -> Result<()> {
+    if let Some(template) = &options.command {
+        return if wait {
+            run_notify_command(template, name, duration_str)
+        } else {
+            spawn_notify_command(template, name, duration_str)
+        };
+    }
+
+    send_native_notification(summary, body, options)
+        .or_else(|_| send_platform_notification(summary, body, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_filled_with_their_values() {
+        let values = [("name", "Deep work"), ("duration", "25m 0s")];
+        assert_eq!(
+            fill_placeholders("{name} done in {duration}", &values),
+            "Deep work done in 25m 0s"
+        );
+        assert_eq!(
+            fill_placeholders("{duration}{duration}", &values),
+            "25m 0s25m 0s"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_and_stray_braces_are_kept() {
+        let values = [("name", "Work")];
+        assert_eq!(
+            fill_placeholders("{name} {elapsed} {} { {name", &values),
+            "Work {elapsed} {} { {name"
+        );
+        assert_eq!(
+            fill_placeholders("no placeholders", &values),
+            "no placeholders"
+        );
+        assert_eq!(fill_placeholders("{name}", &[]), "{name}");
+    }
+
+    #[test]
+    fn values_are_inserted_verbatim() {
+        let values = [("name", "it's \"{duration}\""), ("duration", "5m")];
+        assert_eq!(
+            fill_placeholders("[{name}] {duration}", &values),
+            "[it's \"{duration}\"] 5m"
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn notify_script_quotes_placeholders_for_where_they_sit() {
+        assert_eq!(notify_script("echo {name}"), "echo \"$1\"");
+        assert_eq!(
+            notify_script("echo \"{name} done in {duration}\""),
+            "echo \"$1 done in $2\""
+        );
+        assert_eq!(notify_script("echo '{name}'"), "echo ''\"$1\"''");
+        assert_eq!(
+            notify_script("echo \\' {name} \"\\\"{name}\""),
+            "echo \\' \"$1\" \"\\\"$1\""
+        );
+        assert_eq!(notify_script("echo {other} {"), "echo {other} {");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn notify_script_passes_values_through_the_shell_untouched() {
+        let name = "it's \"a\" $(echo no) `echo no` & more";
+        let output = Command::new("sh")
+            .args([
+                "-c",
+                &notify_script("printf '%s|' {name} \"{name}\" '{name}' {duration}"),
+                "sh",
+                name,
+                "1m 30s",
+            ])
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("{name}|{name}|{name}|1m 30s|")
+        );
+    }
+
+    /// A timer name with everything a shell, AppleScript or PowerShell would
+    /// read as code or as the end of a string.
+    const TRICKY_NAME: &str = "it's \"a\" $(whoami) `date`";
+
+    #[test]
+    fn notify_send_gets_the_text_as_separate_arguments() {
+        let options = NotificationOptions::default();
+        assert_eq!(
+            notify_send_args(TRICKY_NAME, "Done in 5m", &options),
+            [TRICKY_NAME, "Done in 5m"]
+        );
+
+        let options = NotificationOptions {
+            urgency: Urgency::Critical,
+            timeout: Some(Duration::from_millis(1500)),
+            ..Default::default()
+        };
+        assert_eq!(
+            notify_send_args(TRICKY_NAME, "Done in 5m", &options),
+            ["-u", "critical", "-t", "1500", TRICKY_NAME, "Done in 5m"]
+        );
+    }
+
+    #[test]
+    fn osascript_gets_the_text_as_arguments_to_the_script() {
+        assert_eq!(
+            osascript_args(TRICKY_NAME, "Done in 5m"),
+            [
+                "-e",
+                "on run argv",
+                "-e",
+                "display notification (item 2 of argv) with title (item 1 of argv)",
+                "-e",
+                "end run",
+                TRICKY_NAME,
+                "Done in 5m",
+            ]
+        );
+    }
+
+    #[test]
+    fn powershell_quote_doubles_every_kind_of_single_quote() {
+        assert_eq!(
+            powershell_quote(TRICKY_NAME),
+            "'it''s \"a\" $(whoami) `date`'"
+        );
+        assert_eq!(
+            powershell_quote("\u{2018}a\u{2019} \u{201A}b\u{201B}"),
+            "'\u{2018}\u{2018}a\u{2019}\u{2019} \u{201A}\u{201A}b\u{201B}\u{201B}'"
+        );
+        assert_eq!(powershell_quote(""), "''");
+    }
+
+    #[test]
+    fn toast_script_only_contains_the_text_escaped_and_quoted() {
+        let script = toast_script(TRICKY_NAME, "<b>&</b>", &NotificationOptions::default());
+        assert!(script.contains(
+            "$toastXml.LoadXml('<toast duration=\"short\"><visual><binding template=\"ToastText02\">\
+             <text id=\"1\">it&apos;s &quot;a&quot; $(whoami) `date`</text>\
+             <text id=\"2\">&lt;b&gt;&amp;&lt;/b&gt;</text>\
+             </binding></visual></toast>');"
+        ));
+        assert!(!script.contains(TRICKY_NAME));
+    }
+
+    #[test]
+    fn critical_or_long_lived_notifications_get_a_long_toast() {
+        let toast = |urgency, timeout| {
+            let options = NotificationOptions {
+                urgency,
+                timeout,
+                ..Default::default()
+            };
+            (wants_long_toast(&options), toast_script("a", "b", &options))
+        };
+        let secs = |s| Some(Duration::from_secs(s));
+        for (urgency, timeout) in [
+            (Urgency::Critical, None),
+            (Urgency::Normal, Some(Duration::ZERO)),
+            (Urgency::Low, secs(8)),
+        ] {
+            let (long, script) = toast(urgency, timeout);
+            assert!(long);
+            assert!(script.contains("<toast duration=\"long\">"));
+        }
+        for (urgency, timeout) in [(Urgency::Normal, None), (Urgency::Low, secs(7))] {
+            let (long, script) = toast(urgency, timeout);
+            assert!(!long);
+            assert!(script.contains("<toast duration=\"short\">"));
+        }
+    }
+
+    #[test]
+    fn simple_durations_show_units_from_the_largest_non_zero_one() {
+        let format = |secs| format_simple_duration(Duration::from_secs(secs));
+        assert_eq!(format(0), "0s");
+        assert_eq!(format(59), "59s");
+        assert_eq!(format(60), "1m 0s");
+        assert_eq!(format(3599), "59m 59s");
+        assert_eq!(format(3600), "1h 0m 0s");
+        assert_eq!(format(86399), "23h 59m 59s");
+        assert_eq!(format(86400), "1d 0h 0m 0s");
+        assert_eq!(format(3 * 86400 + 4 * 3600 + 5 * 60 + 6), "3d 4h 5m 6s");
+        assert_eq!(format_simple_duration(Duration::from_millis(1999)), "1s");
+    }
+
+    #[test]
+    fn rgb_maps_to_the_nearest_xterm_256_color() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        // Grays land on the 24-step ramp, ends included
+        assert_eq!(rgb_to_ansi256(8, 8, 8), 232);
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+        assert_eq!(rgb_to_ansi256(238, 238, 238), 255);
+        // Cube corners
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(0, 255, 0), 46);
+        assert_eq!(rgb_to_ansi256(0, 0, 255), 21);
+        assert_eq!(rgb_to_ansi256(255, 255, 0), 226);
+        assert_eq!(rgb_to_ansi256(0, 255, 255), 51);
+        assert_eq!(rgb_to_ansi256(255, 0, 255), 201);
+        // In between, the nearest cube level on each channel
+        assert_eq!(rgb_to_ansi256(250, 140, 10), 16 + 36 * 5 + 6 * 2);
+    }
+
+    /// Serializes tests that change the process environment.
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Run `f` with `NO_COLOR` and `FORCE_COLOR` set as given, restoring them
+    /// afterwards.
+    fn with_color_env<T>(
+        no_color: Option<&str>,
+        force_color: Option<&str>,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let saved = [
+            ("NO_COLOR", env::var_os("NO_COLOR")),
+            ("FORCE_COLOR", env::var_os("FORCE_COLOR")),
+        ];
+        let set = |key: &str, value: Option<&std::ffi::OsStr>| match value {
+            // SAFETY: ENV_LOCK keeps other tests from touching the
+            // environment at the same time
+            Some(value) => unsafe { env::set_var(key, value) },
+            None => unsafe { env::remove_var(key) },
+        };
+        set("NO_COLOR", no_color.map(std::ffi::OsStr::new));
+        set("FORCE_COLOR", force_color.map(std::ffi::OsStr::new));
+        let result = f();
+        for (key, value) in &saved {
+            set(key, value.as_deref());
+        }
+        result
+    }
+
+    #[test]
+    fn color_follows_the_terminal_unless_overridden() {
+        with_color_env(None, None, || {
+            assert!(should_color(true));
+            assert!(!should_color(false));
+        });
+        with_color_env(Some("1"), None, || assert!(!should_color(true)));
+        // NO_COLOR wins even when it's empty or FORCE_COLOR is set
+        with_color_env(Some(""), Some("1"), || assert!(!should_color(true)));
+        with_color_env(None, Some("1"), || assert!(should_color(false)));
+        with_color_env(None, Some("0"), || {
+            assert!(should_color(true));
+            assert!(!should_color(false));
+        });
+    }
+
+    #[test]
+    fn precise_durations_show_tenths_below_ten_seconds() {
+        let ms = Duration::from_millis;
+        assert_eq!(format_precise_duration(Duration::ZERO), "0s");
+        assert_eq!(format_precise_duration(ms(300)), "0.3s");
+        assert_eq!(format_precise_duration(ms(2_540)), "2.5s");
+        assert_eq!(format_precise_duration(ms(3_000)), "3s");
+        assert_eq!(format_precise_duration(ms(9_900)), "9.9s");
+        // From ten seconds on, whole units as usual
+        assert_eq!(format_precise_duration(ms(10_700)), "10s");
+        assert_eq!(format_precise_duration(ms(12_307_500)), "3h 25m 7s");
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file_and_cleans_up() {
+        let dir = env::temp_dir().join(format!("tempus-atomic-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        let missing_dir = write_atomic(&dir.join("missing").join("state.json"), "x");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(contents, "second");
+        assert_eq!(entries, ["state.json"]);
+        assert!(missing_dir.is_err());
+        assert!(write_atomic(Path::new("/"), "x").is_err());
+    }
+
+    #[test]
+    fn name_placeholders_use_the_start_time() {
+        use chrono::TimeZone;
+        let start = Local.with_ymd_and_hms(2025, 5, 10, 14, 5, 9).unwrap();
+        let expand = |name| expand_name(name, &start, ClockFormat::TwentyFourHour);
+        assert_eq!(expand("Backup {date}"), "Backup 2025-05-10");
+        assert_eq!(expand("Standup {time}"), "Standup 14:05:09");
+        assert_eq!(expand("{datetime}"), "2025-05-10 14:05:09");
+        assert_eq!(expand("Tea"), "Tea");
+        assert_eq!(
+            expand_name("{time} on {date}", &start, ClockFormat::TwelveHour),
+            "02:05:09 PM on 2025-05-10"
+        );
+        assert_eq!(
+            expand_name("{datetime}", &start, ClockFormat::TwelveHour),
+            "2025-05-10 02:05:09 PM"
+        );
+    }
+
+    /// Run `repeat_spaced` with a fake `wait` that answers `waits` in turn,
+    /// returning what was done and waited for, in order.
+    fn spaced(count: u32, waits: &[bool]) -> Vec<String> {
+        let log = std::cell::RefCell::new(Vec::new());
+        let mut answers = waits.iter();
+        repeat_spaced(
+            count,
+            Duration::from_millis(250),
+            || log.borrow_mut().push("ring".to_string()),
+            |interval| {
+                log.borrow_mut()
+                    .push(format!("wait {}ms", interval.as_millis()));
+                *answers.next().unwrap()
+            },
+        );
+        log.into_inner()
+    }
+
+    #[test]
+    fn repeats_are_spaced_by_the_interval() {
+        assert!(spaced(0, &[]).is_empty());
+        assert_eq!(spaced(1, &[]), ["ring"]);
+        assert_eq!(
+            spaced(3, &[true, true]),
+            ["ring", "wait 250ms", "ring", "wait 250ms", "ring"]
+        );
+    }
+
+    #[test]
+    fn repeats_stop_when_the_wait_is_cut_short() {
+        assert_eq!(spaced(3, &[false]), ["ring", "wait 250ms"]);
+        assert_eq!(
+            spaced(3, &[true, false]),
+            ["ring", "wait 250ms", "ring", "wait 250ms"]
+        );
+    }
+
+    /// What `flash_screen_to` writes when `wait` answers `waits` in turn.
+    fn flashes(waits: &[bool]) -> String {
+        let mut out = Vec::new();
+        let mut answers = waits.iter();
+        flash_screen_to(&mut out, |interval| {
+            assert_eq!(interval, FLASH_INTERVAL);
+            *answers.next().unwrap()
+        });
+        assert_eq!(answers.next(), None, "every wait is used");
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn flashes_toggle_reverse_video_and_always_restore_it() {
+        let flash = format!("{}{}", FLASH_ON, FLASH_OFF);
+        assert_eq!(flashes(&[true; 5]), flash.repeat(3));
+        // A key press while inverted still switches back to normal video
+        assert_eq!(flashes(&[false]), flash);
+        assert_eq!(flashes(&[true, true, false]), flash.repeat(2));
+        // A key press between flashes stops before the next one
+        assert_eq!(flashes(&[true, false]), flash);
+    }
+
+    #[test]
+    fn speech_gets_the_text_as_a_single_argument() {
+        assert_eq!(
+            linux_speakers(TRICKY_NAME),
+            [("spd-say", [TRICKY_NAME]), ("espeak", [TRICKY_NAME])]
+        );
+        assert_eq!(say_args(TRICKY_NAME), [TRICKY_NAME]);
+    }
+
+    #[test]
+    fn speech_script_only_contains_the_text_quoted() {
+        assert_eq!(
+            speech_script(TRICKY_NAME),
+            "Add-Type -AssemblyName System.Speech; \
+             (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('it''s \"a\" $(whoami) `date`')"
+        );
+    }
 }
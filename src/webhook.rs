@@ -0,0 +1,146 @@
+use crate::utils::{fill_placeholders, format_simple_duration};
+use crate::{Result, TempusError};
+use serde_json::json;
+use std::time::Duration;
+
+/// JSON body posted when a timer completes. `text` and `content` carry a
+/// ready-made message for Slack/Mattermost and Discord respectively.
+fn webhook_body(name: &str, duration: Duration) -> String {
+    let duration_str = format_simple_duration(duration);
+    let message = format!("{} completed! (took {})", name, duration_str);
+    json!({
+        "name": name,
+        "duration": duration_str,
+        "duration_secs": duration.as_secs(),
+        "text": message,
+        "content": message,
+    })
+    .to_string()
+}
+
+/// Percent-encode everything but the unreserved URL characters, so a
+/// placeholder value can't change the structure of the URL.
+fn encode_url_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Fill in the `{name}` and `{duration}` placeholders of a `--webhook` URL.
+fn render_webhook_url(template: &str, name: &str, duration: Duration) -> String {
+    let name = encode_url_value(name);
+    let duration = encode_url_value(&format_simple_duration(duration));
+    fill_placeholders(template, &[("name", &name), ("duration", &duration)])
+}
+
+#[cfg(feature = "webhook")]
+fn post_json(url: &str, body: &str) -> Result<()> {
+    // Errors leave out the URL, as webhook URLs often embed a secret token
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(10))
+        .build();
+    match agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => Err(TempusError::Webhook(format!(
+            "server returned {} {}",
+            code,
+            response.status_text()
+        ))),
+        Err(ureq::Error::Transport(transport)) => {
+            let mut reason = transport.kind().to_string();
+            if let Some(message) = transport.message() {
+                reason.push_str(&format!(": {}", message));
+            }
+            if let Some(source) = std::error::Error::source(&transport) {
+                reason.push_str(&format!(": {}", source));
+            }
+            Err(TempusError::Webhook(reason))
+        }
+    }
+}
+
+#[cfg(not(feature = "webhook"))]
+fn post_json(_url: &str, _body: &str) -> Result<()> {
+    Err(TempusError::Webhook(
+        "tempus was built without the `webhook` feature".to_string(),
+    ))
+}
+
+/// POST a completion message for `name` to the `--webhook` URL template.
+fn post_webhook(template: &str, name: &str, duration: Duration) -> Result<()> {
+    let url = render_webhook_url(template, name, duration);
+    post_json(&url, &webhook_body(name, duration))
+}
+
+/// Post to the webhook if `--webhook` is set. A failed request only prints a
+/// warning, as the timer itself has already finished.
+pub fn webhook_completion(template: Option<&str>, name: &str, duration: Duration) {
+    if let Some(template) = template
+        && let Err(e) = post_webhook(template, name, duration)
+    {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_carries_the_name_duration_and_message() {
+        let body: serde_json::Value =
+            serde_json::from_str(&webhook_body("Deep \"work\"", Duration::from_secs(1500)))
+                .unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "name": "Deep \"work\"",
+                "duration": "25m 0s",
+                "duration_secs": 1500,
+                "text": "Deep \"work\" completed! (took 25m 0s)",
+                "content": "Deep \"work\" completed! (took 25m 0s)",
+            })
+        );
+    }
+
+    #[test]
+    fn url_placeholders_are_percent_encoded() {
+        assert_eq!(
+            render_webhook_url(
+                "https://ntfy.sh/timers?title={name}&after={duration}",
+                "Work & rest/2",
+                Duration::from_secs(90),
+            ),
+            "https://ntfy.sh/timers?title=Work%20%26%20rest%2F2&after=1m%2030s"
+        );
+    }
+
+    #[test]
+    fn url_without_placeholders_is_left_alone() {
+        assert_eq!(
+            render_webhook_url(
+                "https://example.com/hook?a=b&c={other}",
+                "x",
+                Duration::ZERO
+            ),
+            "https://example.com/hook?a=b&c={other}"
+        );
+    }
+
+    #[test]
+    fn url_values_keep_only_unreserved_characters() {
+        assert_eq!(encode_url_value("a-Z_0.9~"), "a-Z_0.9~");
+        assert_eq!(encode_url_value("{name}?#"), "%7Bname%7D%3F%23");
+        assert_eq!(encode_url_value("é"), "%C3%A9");
+    }
+}
@@ -0,0 +1,354 @@
+//! End-to-end tests for the `tempus` binary. Runs stop at `--dry-run`, fail
+//! before starting, or use timers of at most a second, so the suite stays fast.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// The binary with colors off and the config and state directories pointed
+/// somewhere empty, so the user's own presets and settings don't leak in.
+fn tempus() -> Command {
+    let isolated = std::env::temp_dir().join("tempus-cli-tests");
+    let mut cmd = Command::cargo_bin("tempus").unwrap();
+    cmd.env("NO_COLOR", "1")
+        .env("XDG_CONFIG_HOME", isolated.join("config"))
+        .env("XDG_STATE_HOME", isolated.join("state"));
+    for var in [
+        "TEMPUS_NAME",
+        "TEMPUS_THEME",
+        "TEMPUS_BELL",
+        "TEMPUS_NOTIFY",
+    ] {
+        cmd.env_remove(var);
+    }
+    cmd
+}
+
+#[test]
+fn timer_dry_run_shows_the_duration() {
+    tempus()
+        .args(["5s", "--dry-run"])
+        .assert()
+        .success()
+        .stdout("Timer: 5s\n");
+}
+
+#[test]
+fn chained_timers_are_planned_in_order() {
+    tempus()
+        .args(["25m", "5m", "-n", "Work", "-n", "Break", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Work: 25m 0s\nBreak: 5m 0s\n"));
+}
+
+#[test]
+fn zero_length_timer_completes_straight_away() {
+    tempus()
+        .args(["0s", "--quiet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Timer completed! (took 0s)\n"));
+}
+
+#[test]
+fn invalid_duration_is_a_usage_error() {
+    tempus()
+        .arg("5x")
+        .assert()
+        .code(2)
+        .stderr("Error: Invalid duration format: 5x\n");
+}
+
+#[test]
+fn missing_duration_is_a_usage_error() {
+    tempus()
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Either DURATION or --preset"));
+}
+
+#[test]
+fn unknown_flags_are_usage_errors() {
+    tempus()
+        .args(["5s", "--no-such-flag"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "unexpected argument '--no-such-flag'",
+        ));
+}
+
+/// Run the binary with `args`, send it SIGINT half a second in, and collect
+/// what it printed.
+#[cfg(unix)]
+fn interrupted(args: &[&str]) -> std::process::Output {
+    let isolated = std::env::temp_dir().join("tempus-cli-tests");
+    let child = std::process::Command::new(assert_cmd::cargo::cargo_bin("tempus"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .env("XDG_CONFIG_HOME", isolated.join("config"))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let killed = std::process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_exits_with_130() {
+    let output = interrupted(&["10s", "--no-bell"]);
+    assert_eq!(output.status.code(), Some(130));
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("Timer interrupted.\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn quiet_timer_reports_the_interrupt() {
+    let output = interrupted(&["10s", "--quiet"]);
+    assert_eq!(output.status.code(), Some(130));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Timer interrupted.\n"
+    );
+}
+
+#[test]
+fn past_countdown_is_rejected() {
+    for target in ["2020-01-01 00:00", "@1700000000"] {
+        tempus()
+            .args(["countdown", target, "--dry-run"])
+            .assert()
+            .code(2)
+            .stderr("Error: Target date/time is in the past\n");
+    }
+}
+
+#[test]
+fn builtin_preset_resolves_to_its_duration() {
+    tempus()
+        .args(["-p", "pomodoro", "--dry-run"])
+        .assert()
+        .success()
+        .stdout("Timer: 25m 0s\n");
+}
+
+#[test]
+fn unknown_preset_falls_through_to_a_duration() {
+    tempus()
+        .args(["-p", "90s", "--dry-run"])
+        .assert()
+        .success()
+        .stdout("Timer: 1m 30s\n");
+
+    tempus()
+        .args(["-p", "nosuch", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr("Error: Invalid duration format: nosuch\n");
+}
+
+#[test]
+fn subcommand_takes_precedence_over_timer_arguments() {
+    tempus()
+        .arg("list-presets")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pomodoro     25m"));
+}
+
+#[test]
+fn theme_comes_from_the_flag_then_the_environment_then_the_default() {
+    let unknown_theme = predicate::str::contains("unknown theme 'bogus'");
+
+    tempus()
+        .args(["5s", "--dry-run"])
+        .assert()
+        .success()
+        .stderr("");
+
+    tempus()
+        .args(["5s", "--dry-run"])
+        .env("TEMPUS_THEME", "bogus")
+        .assert()
+        .success()
+        .stderr(unknown_theme.clone());
+
+    tempus()
+        .args(["5s", "--dry-run", "-t", "plain"])
+        .env("TEMPUS_THEME", "bogus")
+        .assert()
+        .success()
+        .stderr("");
+
+    tempus()
+        .args(["5s", "--dry-run", "-t", "bogus"])
+        .env("TEMPUS_THEME", "plain")
+        .assert()
+        .success()
+        .stderr(unknown_theme);
+}
+
+#[test]
+fn name_defaults_from_the_environment() {
+    tempus()
+        .args(["5s", "--dry-run"])
+        .env("TEMPUS_NAME", "Work")
+        .assert()
+        .success()
+        .stdout("Work: 5s\n");
+}
+
+#[test]
+fn progress_to_stderr_leaves_stdout_to_the_result() {
+    tempus()
+        .args(["1s", "--progress-to-stderr"])
+        .assert()
+        .success()
+        .stdout("Timer completed! (took 1s)\n")
+        .stderr(predicate::str::contains("| Timer |").and(predicate::str::contains("remaining")));
+}
+
+#[cfg(unix)]
+#[test]
+fn notify_cmd_passes_the_name_through_untouched() {
+    let name = "it's \"done\" $(echo injected) `echo injected`\nnext line";
+    tempus()
+        .args(["0s", "--quiet", "-n", name])
+        .args(["--notify-cmd", "printf '[%s]' {name} \"{name}\" '{name}'"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(format!(
+            "[{name}][{name}][{name}]"
+        )));
+}
+
+#[test]
+fn no_bell_overrides_the_default_the_flag_and_the_environment() {
+    let done = "Timer completed! (took 0s)\n";
+    let rung = format!("{}\x07", done);
+
+    tempus()
+        .args(["0s", "--quiet"])
+        .assert()
+        .success()
+        .stdout(rung);
+
+    tempus()
+        .args(["0s", "--quiet", "--no-bell"])
+        .assert()
+        .success()
+        .stdout(done);
+
+    tempus()
+        .args(["0s", "--quiet", "-b", "--no-bell"])
+        .assert()
+        .success()
+        .stdout(done);
+
+    tempus()
+        .args(["0s", "--quiet", "--no-bell"])
+        .env("TEMPUS_BELL", "true")
+        .assert()
+        .success()
+        .stdout(done);
+
+    tempus()
+        .args(["0s", "--quiet", "--big", "--no-bell"])
+        .assert()
+        .success()
+        .stdout(done);
+}
+
+#[test]
+fn countdown_takes_a_timestamp_a_preset_or_a_duration() {
+    tempus()
+        .args(["countdown", "2999-01-01 00:00", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Countdown: 2999-01-01 00:00:00 (in ",
+        ));
+
+    let in_25m = predicate::str::is_match(r"^Countdown: .+ \(in 24m 5\ds\)\n$").unwrap();
+    for args in [
+        ["countdown", "pomodoro", "--dry-run"],
+        ["countdown", "25m", "--dry-run"],
+        ["countdown", "--preset=pomodoro", "--dry-run"],
+    ] {
+        tempus()
+            .args(args)
+            .assert()
+            .success()
+            .stdout(in_25m.clone());
+    }
+
+    tempus()
+        .args(["countdown", "nosuch", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr("Error: Invalid date/time format: nosuch\n");
+}
+
+#[test]
+fn countdown_reports_a_broken_preset_file() {
+    let missing = std::env::temp_dir().join("tempus-cli-tests/no-such-presets.toml");
+    tempus()
+        .args(["countdown", "pomodoro", "--dry-run", "--preset-file"])
+        .arg(&missing)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::starts_with("Error: Preset file error: "));
+}
+
+#[test]
+fn resuming_a_countdown_whose_target_passed_completes_it() {
+    let dir = std::env::temp_dir().join("tempus-cli-tests/state/tempus");
+    std::fs::create_dir_all(&dir).unwrap();
+    let state = dir.join("passed.json");
+    std::fs::write(
+        &state,
+        r#"{"name":"Tea","target":"2020-01-01T00:00:00+00:00"}"#,
+    )
+    .unwrap();
+
+    tempus()
+        .args(["resume", "passed"])
+        .env("TZ", "UTC")
+        .assert()
+        .success()
+        .stdout("Tea completed! (target 2020-01-01 00:00:00 has passed)\n");
+    assert!(!state.exists());
+}
+
+#[test]
+fn status_of_an_unknown_countdown_fails() {
+    tempus()
+        .args(["status", "--id", "nosuch"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("Error: No countdown saved with ID 'nosuch'\n");
+}
+
+#[test]
+fn alarm_dry_run_shows_its_target() {
+    tempus()
+        .args(["alarm", "2999-01-01 07:00", "-n", "Wake up", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Wake up: 2999-01-01 07:00:00 (in ",
+        ));
+    tempus()
+        .args(["alarm", "2020-01-01 00:00", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr("Error: Target date/time is in the past\n");
+}